@@ -0,0 +1,45 @@
+//! Feed arbitrary argv to a parser with every kind of short flag
+//!
+//! The parser must never panic, and errors must render and annotate the
+//! command line without panicking either.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use stoml_args::{ArgType, Args, arg, args};
+
+fn parser() -> Args {
+    args("fuzz")
+        .version("1.0")
+        .plus_minus_toggles()
+        .arg(arg("all").short('a').long("all").flag())
+        .arg(arg("brief").short('b').long("brief").flag())
+        .arg(
+            arg("verbose")
+                .short('v')
+                .long("verbose")
+                .count()
+                .max_count(5),
+        )
+        .arg(arg("out").short('o').long("out"))
+        .arg(arg("num").short('n').long("num").arg_type(ArgType::Integer))
+        .arg(
+            arg("include")
+                .short('I')
+                .long("include")
+                .arg_type(ArgType::Array)
+                .value_delimiter(','),
+        )
+        .arg(arg("define").short('D').arg_type(ArgType::KeyValue))
+        .arg(arg("point").short('p').value_names(&["x", "y"]))
+}
+
+fuzz_target!(|argv: Vec<String>| {
+    if let Err(e) = parser().parse_from(argv.clone()) {
+        let _ = e.to_string();
+        let _ = e.annotated(&argv);
+        if let Some(position) = e.position() {
+            assert!(position < argv.len(), "{:?} points past {:?}", e, argv);
+        }
+    }
+});
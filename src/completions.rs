@@ -0,0 +1,292 @@
+use std::io::{self, Write};
+
+use crate::{Arg, ArgType, Args};
+
+/// Shells `Args::generate_completion` can write scripts for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Args {
+    /// Write a shell completion script for this program
+    ///
+    /// The script completes long and short flags (including the automatic
+    /// `--help`, `--version` and `--config`), subcommand names, and flag
+    /// values by type: possible values, `true`/`false` after `--flag=` for
+    /// bool flags, and file names for `ArgType::Path`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let parser = args("myapp");
+    /// let mut file = std::fs::File::create("myapp.bash")?;
+    /// parser.generate_completion(Shell::Bash, &mut file)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_completion(&self, shell: Shell, out: &mut dyn Write) -> io::Result<()> {
+        let options = self.completion_options();
+        match shell {
+            Shell::Bash => self.write_bash(&options, out),
+            Shell::Zsh => self.write_zsh(&options, out),
+            Shell::Fish => self.write_fish(&options, out),
+        }
+    }
+
+    /// Flag args, plus the auto flags added at parse time
+    fn completion_options(&self) -> Vec<Arg> {
+        let mut options: Vec<Arg> = self
+            .args
+            .iter()
+            .filter(|a| !a.positional && !a.experimental)
+            .cloned()
+            .collect();
+        if self.auto_config {
+            options.push(
+                Arg::new("config")
+                    .short('c')
+                    .long("config")
+                    .help("Path to configuration file"),
+            );
+        }
+        if self.auto_help {
+            options.push(
+                Arg::new("help")
+                    .short('h')
+                    .long("help")
+                    .flag()
+                    .help("Print help information"),
+            );
+        }
+        if self.auto_version && self.version.is_some() {
+            options.push(
+                Arg::new("version")
+                    .short('V')
+                    .long("version")
+                    .flag()
+                    .help("Print version information"),
+            );
+        }
+        options
+    }
+
+    fn write_bash(&self, options: &[Arg], out: &mut dyn Write) -> io::Result<()> {
+        let func = function_name(&self.name);
+
+        let mut words = Vec::new();
+        for arg in options {
+            if let Some(l) = &arg.long {
+                words.push(format!("--{}", l));
+            }
+            if let Some(c) = arg.short {
+                words.push(format!("-{}", c));
+            }
+        }
+        words.extend(self.subcommands.iter().map(|s| s.name.clone()));
+
+        writeln!(out, "_{}() {{", func)?;
+        writeln!(out, "    local cur prev")?;
+        writeln!(out, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+        writeln!(out, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+        // `--flag=value` is split at the `=`, so match on `--flag=` instead
+        writeln!(out, "    if [[ \"$prev\" == \"=\" ]]; then")?;
+        writeln!(out, "        prev=\"${{COMP_WORDS[COMP_CWORD-2]}}=\"")?;
+        writeln!(out, "    fi")?;
+        writeln!(out)?;
+        writeln!(out, "    case \"$prev\" in")?;
+        for arg in options {
+            let compgen = match value_hint(arg) {
+                ValueHint::Any => continue,
+                ValueHint::Values(values) => format!("-W \"{}\"", values.join(" ")),
+                ValueHint::Files => "-f".to_string(),
+            };
+            let mut flags = Vec::new();
+            if let Some(l) = &arg.long {
+                if takes_value(arg) {
+                    flags.push(format!("--{}", l));
+                }
+                flags.push(format!("--{}=", l));
+            }
+            if let Some(c) = arg.short
+                && takes_value(arg)
+            {
+                flags.push(format!("-{}", c));
+            }
+            if flags.is_empty() {
+                continue;
+            }
+            writeln!(out, "        {})", flags.join("|"))?;
+            writeln!(
+                out,
+                "            COMPREPLY=($(compgen {} -- \"$cur\"))",
+                compgen
+            )?;
+            writeln!(out, "            return 0")?;
+            writeln!(out, "            ;;")?;
+        }
+        writeln!(out, "    esac")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "    COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+            words.join(" ")
+        )?;
+        writeln!(out, "}}")?;
+        writeln!(out, "complete -F _{} {}", func, self.name)
+    }
+
+    fn write_zsh(&self, options: &[Arg], out: &mut dyn Write) -> io::Result<()> {
+        let func = function_name(&self.name);
+
+        writeln!(out, "#compdef {}", self.name)?;
+        writeln!(out)?;
+        writeln!(out, "_{}() {{", func)?;
+        writeln!(out, "    _arguments \\")?;
+        for arg in options {
+            let help = match &arg.help {
+                Some(h) => format!("[{}]", zsh_escape(h)),
+                None => String::new(),
+            };
+            let name = zsh_escape(&arg.name);
+            let value = match value_hint(arg) {
+                ValueHint::Any => format!(":{}:", name),
+                ValueHint::Values(values) => format!(":{}:({})", name, values.join(" ")),
+                ValueHint::Files => format!(":{}:_files", name),
+            };
+            let spec = match (arg.short, &arg.long) {
+                (Some(c), Some(l)) if takes_value(arg) => {
+                    format!("'(-{c} --{l})'{{-{c},--{l}}}'{help}{value}'")
+                }
+                // A bool flag only takes a value attached with `=`
+                (Some(c), Some(l)) if is_bool_with_hint(arg) => {
+                    format!("'(-{c} --{l})-{c}{help}' '(-{c} --{l})--{l}=-{help}{value}'")
+                }
+                (Some(c), Some(l)) => format!("'(-{c} --{l})'{{-{c},--{l}}}'{help}'"),
+                (Some(c), None) if takes_value(arg) => format!("'-{c}{help}{value}'"),
+                (Some(c), None) => format!("'-{c}{help}'"),
+                (None, Some(l)) if takes_value(arg) => format!("'--{l}{help}{value}'"),
+                (None, Some(l)) if is_bool_with_hint(arg) => {
+                    format!("'--{l}=-{help}{value}'")
+                }
+                (None, Some(l)) => format!("'--{l}{help}'"),
+                (None, None) => continue,
+            };
+            writeln!(out, "        {} \\", spec)?;
+        }
+        if !self.subcommands.is_empty() {
+            let names: Vec<&str> = self.subcommands.iter().map(|s| s.name.as_str()).collect();
+            writeln!(out, "        '1:command:({})' \\", names.join(" "))?;
+        }
+        writeln!(out, "        '*::arg:_files'")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(out, "_{} \"$@\"", func)
+    }
+
+    fn write_fish(&self, options: &[Arg], out: &mut dyn Write) -> io::Result<()> {
+        for arg in options {
+            let mut line = format!("complete -c {}", self.name);
+            if let Some(c) = arg.short {
+                line.push_str(&format!(" -s {}", c));
+            }
+            if let Some(l) = &arg.long {
+                line.push_str(&format!(" -l {}", l));
+            }
+            if let Some(h) = &arg.help {
+                line.push_str(&format!(" -d '{}'", fish_escape(h)));
+            }
+            if takes_value(arg) {
+                line.push_str(" -r");
+                match value_hint(arg) {
+                    ValueHint::Any => {}
+                    ValueHint::Values(values) => {
+                        line.push_str(&format!(" -f -a '{}'", values.join(" ")))
+                    }
+                    ValueHint::Files => line.push_str(" -F"),
+                }
+            }
+            writeln!(out, "{}", line)?;
+
+            // A bool flag only takes a value attached with `=`
+            if let Some(l) = &arg.long
+                && is_bool_with_hint(arg)
+            {
+                writeln!(
+                    out,
+                    "complete -c {} -n \"string match -q -- '--{l}=*' (commandline -ct)\" -f -a '--{l}=true --{l}=false'",
+                    self.name
+                )?;
+            }
+        }
+        for sub in &self.subcommands {
+            let mut line = format!(
+                "complete -c {} -n '__fish_use_subcommand' -f -a {}",
+                self.name, sub.name
+            );
+            if let Some(about) = &sub.about {
+                line.push_str(&format!(" -d '{}'", fish_escape(about)));
+            }
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a flag's value completes to, shared by all shells
+enum ValueHint {
+    /// Anything; the shell offers nothing specific
+    Any,
+    /// One of a fixed list of words
+    Values(Vec<String>),
+    /// A file name
+    Files,
+}
+
+/// Pick the completion for a flag's value from its possible values and type
+///
+/// The automatic `--help` and `--version` flags get none.
+fn value_hint(arg: &Arg) -> ValueHint {
+    if !arg.possible_values.is_empty() {
+        return ValueHint::Values(arg.possible_values.clone());
+    }
+    match arg.arg_type {
+        ArgType::Bool if matches!(arg.name.as_str(), "help" | "version") => ValueHint::Any,
+        ArgType::Bool => ValueHint::Values(vec!["true".to_string(), "false".to_string()]),
+        ArgType::Path => ValueHint::Files,
+        _ => ValueHint::Any,
+    }
+}
+
+/// Whether a bool flag completes `true`/`false` after `--flag=`
+fn is_bool_with_hint(arg: &Arg) -> bool {
+    arg.arg_type == ArgType::Bool && matches!(value_hint(arg), ValueHint::Values(_))
+}
+
+/// Whether the flag is followed by a value
+fn takes_value(arg: &Arg) -> bool {
+    !matches!(arg.arg_type, ArgType::Bool | ArgType::Count)
+}
+
+/// A shell function name derived from the program name
+fn function_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Escape text for a single-quoted zsh `_arguments` spec
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+/// Escape text for a single-quoted fish string
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use stoml::{Table, Value};
+
+use crate::cycle::CycleGuard;
+use crate::error::{Error, Result};
+
+/// Key listing other config files to load before this one
+const INCLUDE_KEY: &str = "include";
+
+/// Load a config file, resolving `include = [...]` directives
+///
+/// Included files are loaded first (relative to the including file's
+/// directory) and the including file's own keys are merged over them, so
+/// the including file wins. The top-level `include` key is reserved: it is
+/// never merged as a value, and anything but an array of strings is an
+/// `Error::InvalidInclude`. `guard` holds the files currently being loaded
+/// and is used to detect include cycles.
+pub fn load_with_includes(path: &Path, guard: &mut CycleGuard<PathBuf>) -> Result<Table> {
+    let canonical = path.canonicalize()?;
+
+    let mut own = stoml::parse_file(path)?;
+    let includes = match own.remove(INCLUDE_KEY) {
+        Some(Value::Array(a)) if a.iter().all(|v| v.as_str().is_some()) => a,
+        Some(_) => {
+            return Err(Error::InvalidInclude {
+                path: path.display().to_string(),
+            });
+        }
+        None => return Ok(own),
+    };
+
+    if !guard.enter(canonical) {
+        return Err(Error::ConfigCycle {
+            path: path.display().to_string(),
+        });
+    }
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+    let mut table = Table::new();
+    for include in includes.iter().filter_map(|v| v.as_str()) {
+        let included = load_with_includes(&base_dir.join(include), guard)?;
+        merge_tables(&mut table, included);
+    }
+    guard.leave();
+
+    merge_tables(&mut table, own);
+    Ok(table)
+}
+
+/// Deep-merge `overlay` into `base`, with `overlay` winning on conflicts
+pub fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(inner)) => merge_tables(existing, inner),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Render a table as a TOML document
+///
+/// Plain values come first, then each nested table as a `[section]` with
+/// its dotted path. Arrays are written inline.
+pub fn to_toml_string(table: &Table) -> String {
+    let mut out = String::new();
+    write_table(&mut out, table, &[]);
+    out
+}
+
+fn write_table(out: &mut String, table: &Table, path: &[&str]) {
+    for (key, value) in table.iter() {
+        if !value.is_table() {
+            out.push_str(&format!("{} = {}\n", toml_key(key), value));
+        }
+    }
+    for (key, value) in table.iter() {
+        if let Some(inner) = value.as_table() {
+            let mut inner_path = path.to_vec();
+            inner_path.push(key);
+            // Tables holding only other tables are implied by their children
+            let implied = !inner.is_empty() && inner.values().all(|v| v.is_table());
+            if !implied {
+                let header: Vec<String> = inner_path.iter().map(|k| toml_key(k)).collect();
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[{}]\n", header.join(".")));
+            }
+            write_table(out, inner, &inner_path);
+        }
+    }
+}
+
+/// A key as written in TOML: bare if possible, quoted otherwise
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
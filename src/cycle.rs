@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+/// The chain of nodes currently being visited, used to spot cycles
+///
+/// Push a node with `enter` before descending into it and `leave` once done.
+/// `enter` refuses a node that is already on the chain.
+#[derive(Debug)]
+pub struct CycleGuard<T> {
+    stack: Vec<T>,
+}
+
+impl<T: PartialEq> CycleGuard<T> {
+    pub fn new() -> Self {
+        CycleGuard { stack: Vec::new() }
+    }
+
+    /// Start visiting `node`; returns false if that would close a cycle
+    pub fn enter(&mut self, node: T) -> bool {
+        if self.stack.contains(&node) {
+            return false;
+        }
+        self.stack.push(node);
+        true
+    }
+
+    /// Finish visiting the most recently entered node
+    pub fn leave(&mut self) {
+        self.stack.pop();
+    }
+
+    /// The nodes currently being visited, outermost first
+    pub fn chain(&self) -> &[T] {
+        &self.stack
+    }
+}
+
+/// Find a cycle in a graph given as node -> successors
+///
+/// Returns the nodes along the cycle with the first node repeated at the end
+/// (`a -> b -> a`), or None if the graph is acyclic. Nodes are tried in
+/// sorted order so the result is stable.
+pub fn find_cycle<'a>(edges: &HashMap<&'a str, Vec<&'a str>>) -> Option<Vec<&'a str>> {
+    let mut starts: Vec<&str> = edges.keys().copied().collect();
+    starts.sort();
+
+    let mut done = Vec::new();
+    for start in starts {
+        let mut guard = CycleGuard::new();
+        if let Some(cycle) = visit(start, edges, &mut guard, &mut done) {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+fn visit<'a>(
+    node: &'a str,
+    edges: &HashMap<&'a str, Vec<&'a str>>,
+    guard: &mut CycleGuard<&'a str>,
+    done: &mut Vec<&'a str>,
+) -> Option<Vec<&'a str>> {
+    if done.contains(&node) {
+        return None;
+    }
+    if !guard.enter(node) {
+        let chain = guard.chain();
+        let from = chain.iter().position(|n| *n == node).unwrap_or(0);
+        let mut cycle = chain[from..].to_vec();
+        cycle.push(node);
+        return Some(cycle);
+    }
+    for next in edges.get(node).into_iter().flatten() {
+        if let Some(cycle) = visit(next, edges, guard, done) {
+            return Some(cycle);
+        }
+    }
+    guard.leave();
+    done.push(node);
+    None
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use stoml::Value;
+
+use crate::{Error, Matches, Result};
+
+impl Matches {
+    /// Deserialize all values into a user type (`serde` feature)
+    ///
+    /// The values are nested with `to_table` first, so dotted keys such as
+    /// `server.port` fill in nested structs.
+    ///
+    /// # Example
+    /// ```
+    /// # use serde::Deserialize;
+    /// # use stoml_args::Matches;
+    /// # fn main() -> stoml_args::Result<()> {
+    /// # let matches = Matches::from_toml_str("verbose = true\n[server]\nport = 80\n", "myapp")?;
+    /// #[derive(Deserialize)]
+    /// struct Config { verbose: bool, server: Server }
+    /// # #[derive(Deserialize)]
+    /// # struct Server { port: i64 }
+    ///
+    /// let cfg: Config = matches.deserialize()?;
+    /// # assert!(cfg.verbose);
+    /// # assert_eq!(cfg.server.port, 80);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        T::deserialize(ValueDeserializer(Value::Table(self.to_table())))
+            .map_err(|e| Error::Deserialize { message: e.0 })
+    }
+}
+
+/// A deserialization failure, converted to `Error::Deserialize`
+#[derive(Debug)]
+struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+/// Feeds a TOML value to a serde visitor
+struct ValueDeserializer(Value);
+
+impl<'de> IntoDeserializer<'de, DeError> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_string(s),
+            Value::Integer(i) => visitor.visit_i64(i),
+            Value::Float(f) => visitor.visit_f64(f),
+            Value::Boolean(b) => visitor.visit_bool(b),
+            Value::DateTime(dt) => visitor.visit_string(dt.to_string()),
+            Value::Array(a) => {
+                let mut seq = SeqDeserializer::new(a.into_iter().map(ValueDeserializer));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Value::Table(t) => {
+                let entries = t.into_iter().map(|(k, v)| (k, ValueDeserializer(v)));
+                let mut map = MapDeserializer::new(entries);
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    // A value that is present is always `Some`; absent keys become `None`
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    // Only unit variants, spelled as strings (`mode = "fast"`)
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, DeError> {
+        match self.0 {
+            Value::String(s) => visitor.visit_enum(s.into_deserializer()),
+            other => Err(de::Error::custom(format!(
+                "expected a string for an enum, found {}",
+                other.type_name()
+            ))),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
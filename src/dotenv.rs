@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+/// Load a `.env` file of `KEY=value` lines
+///
+/// A missing file yields an empty map.
+pub fn load(path: &Path) -> io::Result<HashMap<String, String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(parse(&content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashMap::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parse `.env` content
+///
+/// Supports `#` comments, an optional `export ` prefix, and single or double
+/// quoted values. Double-quoted values understand `\n`, `\"` and `\\`
+/// escapes; single-quoted values are taken literally. Lines without `=` are
+/// skipped.
+pub fn parse(content: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        vars.insert(key.to_string(), parse_value(value.trim()));
+    }
+
+    vars
+}
+
+/// Parse the value part of a line, handling quotes and inline comments
+fn parse_value(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some(other) => out.push(other),
+                    None => break,
+                },
+                _ => out.push(c),
+            }
+        }
+        return out;
+    }
+
+    if let Some(inner) = value.strip_prefix('\'') {
+        return inner.split('\'').next().unwrap_or("").to_string();
+    }
+
+    // Unquoted: a " #" starts a comment
+    match value.find(" #") {
+        Some(pos) => value[..pos].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
@@ -1,4 +1,9 @@
 use std::fmt;
+use std::io::IsTerminal;
+
+const RED_BOLD: &str = "\x1b[1;31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
 
 /// Result type for argument parsing
 pub type Result<T> = std::result::Result<T, Error>;
@@ -10,36 +15,147 @@ pub enum Error {
     MissingRequired { name: String },
 
     /// An unknown flag was provided
-    UnknownFlag { flag: String },
+    ///
+    /// `position` is the index of the offending token, when known.
+    UnknownFlag {
+        flag: String,
+        position: Option<usize>,
+    },
+
+    /// An unknown flag was provided that is close to a known one
+    UnknownFlagSuggest {
+        flag: String,
+        suggestion: String,
+        position: Option<usize>,
+    },
+
+    /// An abbreviated long flag matched more than one argument
+    AmbiguousFlag {
+        flag: String,
+        candidates: Vec<String>,
+    },
 
     /// An argument expected a value but none was provided
     MissingValue { name: String },
 
+    /// A value was given as a separate token to a flag that needs `--flag=value`
+    RequiresEquals { flag: String },
+
     /// Failed to parse a value
+    ///
+    /// `position` is the index of the offending token, when known.
     InvalidValue {
         name: String,
         value: String,
         expected: &'static str,
+        position: Option<usize>,
     },
 
     /// Duplicate value for a non-array argument
     DuplicateValue { name: String },
 
+    /// A numeric value was outside the argument's range
+    OutOfRange {
+        name: String,
+        value: String,
+        min: String,
+        max: String,
+    },
+
+    /// A count flag was given more times than its `max_count`
+    CountExceeded { name: String, max: i64 },
+
     /// A positional argument was missing
     MissingPositional { name: String, position: usize },
 
     /// Too many positional arguments
     TooManyPositional { max: usize, got: usize },
 
+    /// Fewer positional arguments than `Args::min_positionals`
+    TooFewPositional { min: usize, got: usize },
+
+    /// A value isn't one of the argument's possible values
+    InvalidChoice {
+        name: String,
+        value: String,
+        allowed: Vec<String>,
+    },
+
+    /// A key/value argument was given a value without the separator
+    MissingSeparator {
+        name: String,
+        value: String,
+        separator: char,
+    },
+
+    /// A value doesn't match the argument's regex pattern
+    PatternMismatch {
+        name: String,
+        value: String,
+        pattern: String,
+    },
+
+    /// A value was rejected by the argument's validator
+    Validation { name: String, message: String },
+
+    /// A token in subcommand position didn't name any subcommand
+    ///
+    /// `suggestion` is the closest subcommand name or alias, if any is close.
+    UnknownSubcommand {
+        name: String,
+        available: Vec<String>,
+        suggestion: Option<String>,
+    },
+
+    /// A positional group ended partway through a record
+    PartialRecord {
+        name: String,
+        expected: usize,
+        got: usize,
+    },
+
+    /// Only some of a set of all-or-none arguments were provided
+    IncompleteGroup {
+        present: Vec<String>,
+        missing: Vec<String>,
+    },
+
+    /// None of a group of arguments was provided, but one is required
+    MissingRequiredGroup { names: Vec<String> },
+
+    /// More than one of a group of mutually exclusive arguments was provided
+    ConflictingArgs { names: Vec<String> },
+
+    /// An argument was given without another one it requires
+    MissingDependency { arg: String, needs: String },
+
     /// Required config file is missing
     MissingConfig { path: String },
 
+    /// An argument was defined in a way that cannot be parsed
+    InvalidDefinition { reason: String },
+
+    /// Several definition problems found by `Args::validate`
+    InvalidDefinitions { reasons: Vec<String> },
+
+    /// Config files include each other in a cycle
+    ConfigCycle { path: String },
+
+    /// A config file's `include` is not an array of paths
+    InvalidInclude { path: String },
+
+    /// A deprecated argument was used after its removal version
+    RemovedArg { name: String, version: String },
+
     /// Help was requested
     Help(String),
 
     /// Version was requested
     Version(String),
 
+    /// Values couldn't be deserialized into the requested type
+    Deserialize { message: String },
+
     /// TOML parsing error
     Toml(stoml::Error),
 
@@ -53,16 +169,46 @@ impl fmt::Display for Error {
             Error::MissingRequired { name } => {
                 write!(f, "required argument '{}' was not provided", name)
             }
-            Error::UnknownFlag { flag } => {
+            Error::UnknownFlag { flag, .. } => {
                 write!(f, "unknown flag '{}'", flag)
             }
+            Error::UnknownFlagSuggest {
+                flag, suggestion, ..
+            } => {
+                write!(
+                    f,
+                    "unknown flag '{}' (did you mean '{}'?)",
+                    flag, suggestion
+                )
+            }
+            Error::AmbiguousFlag { flag, candidates } => {
+                write!(
+                    f,
+                    "ambiguous flag '{}': could be {}",
+                    flag,
+                    candidates.join(", ")
+                )
+            }
             Error::MissingValue { name } => {
                 write!(f, "argument '{}' requires a value", name)
             }
+            Error::RequiresEquals { flag } => {
+                let form = if flag.starts_with("--") {
+                    "=VALUE"
+                } else {
+                    "VALUE"
+                };
+                write!(
+                    f,
+                    "'{}' requires its value attached, as '{}{}'",
+                    flag, flag, form
+                )
+            }
             Error::InvalidValue {
                 name,
                 value,
                 expected,
+                ..
             } => {
                 write!(
                     f,
@@ -73,6 +219,25 @@ impl fmt::Display for Error {
             Error::DuplicateValue { name } => {
                 write!(f, "argument '{}' cannot be specified multiple times", name)
             }
+            Error::OutOfRange {
+                name,
+                value,
+                min,
+                max,
+            } => {
+                write!(
+                    f,
+                    "invalid value '{}' for '{}': must be between {} and {}",
+                    value, name, min, max
+                )
+            }
+            Error::CountExceeded { name, max } => {
+                write!(
+                    f,
+                    "argument '{}' cannot be given more than {} times",
+                    name, max
+                )
+            }
             Error::MissingPositional { name, position } => {
                 write!(
                     f,
@@ -80,6 +245,67 @@ impl fmt::Display for Error {
                     name, position
                 )
             }
+            Error::InvalidChoice {
+                name,
+                value,
+                allowed,
+            } => {
+                write!(
+                    f,
+                    "invalid value '{}' for '{}': possible values are {}",
+                    value,
+                    name,
+                    allowed.join(", ")
+                )
+            }
+            Error::MissingSeparator {
+                name,
+                value,
+                separator,
+            } => {
+                write!(
+                    f,
+                    "invalid value '{}' for '{}': expected KEY{}VALUE",
+                    value, name, separator
+                )
+            }
+            Error::PatternMismatch {
+                name,
+                value,
+                pattern,
+            } => {
+                write!(
+                    f,
+                    "invalid value '{}' for '{}': must match pattern '{}'",
+                    value, name, pattern
+                )
+            }
+            Error::Validation { name, message } => {
+                write!(f, "invalid value for '{}': {}", name, message)
+            }
+            Error::UnknownSubcommand {
+                name,
+                available,
+                suggestion: Some(suggestion),
+            } => {
+                write!(
+                    f,
+                    "unknown subcommand '{}' (did you mean '{}'? available: {})",
+                    name,
+                    suggestion,
+                    available.join(", ")
+                )
+            }
+            Error::UnknownSubcommand {
+                name, available, ..
+            } => {
+                write!(
+                    f,
+                    "unknown subcommand '{}' (available: {})",
+                    name,
+                    available.join(", ")
+                )
+            }
             Error::TooManyPositional { max, got } => {
                 write!(
                     f,
@@ -87,9 +313,70 @@ impl fmt::Display for Error {
                     max, got
                 )
             }
+            Error::TooFewPositional { min, got } => {
+                write!(
+                    f,
+                    "too few positional arguments: expected at least {}, got {}",
+                    min, got
+                )
+            }
+            Error::PartialRecord {
+                name,
+                expected,
+                got,
+            } => {
+                write!(
+                    f,
+                    "incomplete '{}' record: expected {} values, got {}",
+                    name, expected, got
+                )
+            }
+            Error::IncompleteGroup { present, missing } => {
+                write!(
+                    f,
+                    "'{}' must be used together with '{}'",
+                    present.join("', '"),
+                    missing.join("', '")
+                )
+            }
+            Error::MissingRequiredGroup { names } => {
+                write!(f, "one of '{}' must be provided", names.join("', '"))
+            }
+            Error::ConflictingArgs { names } => {
+                write!(f, "only one of '{}' may be provided", names.join("', '"))
+            }
+            Error::MissingDependency { arg, needs } => {
+                write!(f, "argument '{}' requires '{}' to be provided", arg, needs)
+            }
             Error::MissingConfig { path } => {
                 write!(f, "required config file '{}' not found", path)
             }
+            Error::InvalidDefinition { reason } => {
+                write!(f, "invalid argument definition: {}", reason)
+            }
+            Error::InvalidDefinitions { reasons } => {
+                write!(f, "invalid argument definitions:")?;
+                for reason in reasons {
+                    write!(f, "\n  - {}", reason)?;
+                }
+                Ok(())
+            }
+            Error::ConfigCycle { path } => {
+                write!(f, "config file '{}' includes itself", path)
+            }
+            Error::InvalidInclude { path } => {
+                write!(
+                    f,
+                    "config file '{}': 'include' must be an array of paths",
+                    path
+                )
+            }
+            Error::RemovedArg { name, version } => {
+                write!(f, "argument '{}' was removed in version {}", name, version)
+            }
+            Error::Deserialize { message } => {
+                write!(f, "failed to deserialize config: {}", message)
+            }
             Error::Help(msg) => write!(f, "{}", msg),
             Error::Version(msg) => write!(f, "{}", msg),
             Error::Toml(e) => write!(f, "TOML error: {}", e),
@@ -136,17 +423,167 @@ impl Error {
         self.is_help() || self.is_version()
     }
 
+    /// Index of the offending token in the parsed arguments, if known
+    ///
+    /// Set on `UnknownFlag`, `UnknownFlagSuggest` and `InvalidValue` errors
+    /// raised while reading the command line.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            Error::UnknownFlag { position, .. }
+            | Error::UnknownFlagSuggest { position, .. }
+            | Error::InvalidValue { position, .. } => *position,
+            _ => None,
+        }
+    }
+
+    /// Record the offending token's index, unless one is already set
+    pub(crate) fn at_position(mut self, index: usize) -> Self {
+        if let Error::UnknownFlag { position, .. }
+        | Error::UnknownFlagSuggest { position, .. }
+        | Error::InvalidValue { position, .. } = &mut self
+            && position.is_none()
+        {
+            *position = Some(index);
+        }
+        self
+    }
+
+    /// Shift a known token index by `offset` (for subcommand arguments)
+    pub(crate) fn offset_position(mut self, offset: usize) -> Self {
+        if let Error::UnknownFlag { position, .. }
+        | Error::UnknownFlagSuggest { position, .. }
+        | Error::InvalidValue { position, .. } = &mut self
+            && let Some(index) = position
+        {
+            *index += offset;
+        }
+        self
+    }
+
+    /// Render the error with the command line, marking the offending token
+    ///
+    /// `argv` is what was parsed, without the program name (the arguments
+    /// given to `Args::parse_from`). Errors without a known position render
+    /// as just the `error: ...` line.
+    ///
+    /// # Example
+    /// ```text
+    /// error: invalid value 'abc' for 'port': expected an integer
+    ///   --verbose --port abc
+    ///                    ^^^
+    /// ```
+    pub fn annotated(&self, argv: &[String]) -> String {
+        let mut out = format!("error: {}", self);
+        let Some(index) = self.position().filter(|&i| i < argv.len()) else {
+            return out;
+        };
+        let offset: usize = argv[..index].iter().map(|a| a.chars().count() + 1).sum();
+        let width = argv[index].chars().count().max(1);
+        out.push_str(&format!(
+            "\n  {}\n  {}{}",
+            argv.join(" "),
+            " ".repeat(offset),
+            "^".repeat(width)
+        ));
+        out
+    }
+
+    /// The status code `exit` uses for this error
+    ///
+    /// Follows sysexits.h: 0 for help/version, 64 (`EX_USAGE`) for mistakes
+    /// on the command line, 74 (`EX_IOERR`) for IO errors and 1 otherwise.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Help(_) | Error::Version(_) => 0,
+            Error::MissingRequired { .. }
+            | Error::UnknownFlag { .. }
+            | Error::UnknownFlagSuggest { .. }
+            | Error::AmbiguousFlag { .. }
+            | Error::MissingValue { .. }
+            | Error::RequiresEquals { .. }
+            | Error::InvalidValue { .. }
+            | Error::OutOfRange { .. }
+            | Error::DuplicateValue { .. }
+            | Error::CountExceeded { .. }
+            | Error::MissingPositional { .. }
+            | Error::TooManyPositional { .. }
+            | Error::TooFewPositional { .. }
+            | Error::InvalidChoice { .. }
+            | Error::MissingSeparator { .. }
+            | Error::PatternMismatch { .. }
+            | Error::Validation { .. }
+            | Error::UnknownSubcommand { .. }
+            | Error::PartialRecord { .. }
+            | Error::IncompleteGroup { .. }
+            | Error::MissingRequiredGroup { .. }
+            | Error::ConflictingArgs { .. }
+            | Error::MissingDependency { .. }
+            | Error::RemovedArg { .. } => 64,
+            Error::Io(_) => 74,
+            _ => 1,
+        }
+    }
+
     /// Exit the program with the appropriate status code
     ///
-    /// Prints help/version to stdout with exit code 0,
-    /// prints errors to stderr with exit code 1.
+    /// Prints help/version to stdout, and errors to stderr. The status code
+    /// comes from `exit_code`.
     pub fn exit(&self) -> ! {
+        self.exit_with(self.exit_code())
+    }
+
+    /// Like `exit`, but with a fixed status code for errors
+    ///
+    /// Help and version requests still exit with 0.
+    pub fn exit_with(&self, code: i32) -> ! {
         if self.is_info_request() {
             println!("{}", self);
             std::process::exit(0);
         } else {
-            eprintln!("error: {}", self);
-            std::process::exit(1);
+            self.eprint_colored();
+            std::process::exit(code);
         }
     }
+
+    /// Print the error to stderr as `error: ...`
+    ///
+    /// When stderr is a terminal and `NO_COLOR` is unset, "error:" is shown
+    /// in red and any did-you-mean suggestion in green.
+    pub fn eprint_colored(&self) {
+        eprintln!("{}", self.render(stderr_color()));
+    }
+
+    /// The error line printed by `eprint_colored`, with or without colors
+    pub(crate) fn render(&self, color: bool) -> String {
+        if !color {
+            return format!("error: {}", self);
+        }
+        let message = match self {
+            Error::UnknownFlagSuggest {
+                flag, suggestion, ..
+            } => format!(
+                "unknown flag '{}' (did you mean '{}{}{}'?)",
+                flag, GREEN, suggestion, RESET
+            ),
+            Error::UnknownSubcommand {
+                name,
+                available,
+                suggestion: Some(suggestion),
+            } => format!(
+                "unknown subcommand '{}' (did you mean '{}{}{}'? available: {})",
+                name,
+                GREEN,
+                suggestion,
+                RESET,
+                available.join(", ")
+            ),
+            other => other.to_string(),
+        };
+        format!("{}error:{} {}", RED_BOLD, RESET, message)
+    }
+}
+
+/// Whether stderr should get colors: a terminal, and `NO_COLOR` unset
+pub(crate) fn stderr_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stderr().is_terminal()
 }
@@ -0,0 +1,25 @@
+/// A milestone in resolving argument values, reported to `Args::on_parse_event`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseEvent {
+    /// A missing config file was created from the template
+    ConfigCreated { path: String },
+    /// A config file was loaded
+    ConfigLoaded { path: String },
+    /// An absent argument was read from an environment variable
+    EnvResolved { var: String },
+    /// A missing required argument was read from an interactive prompt
+    Prompted { name: String },
+    /// An argument was set because another argument implies it
+    Implied { name: String, by: String },
+    /// An absent argument was given its default by `Matches::with_defaults`
+    DefaultApplied { name: String },
+}
+
+/// Which informational flag was given, reported to `Args::intercept_info`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InfoKind {
+    /// `--help` or `--help-all`
+    Help,
+    /// `--version`
+    Version,
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use stoml::Value;
+
+use crate::{Error, Matches, Result};
+
+/// The type a field must have for `Matches::extract`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// A string
+    String,
+    /// An integer
+    Integer,
+    /// A float (integers are accepted and converted)
+    Float,
+    /// A boolean
+    Bool,
+    /// An array
+    Array,
+    /// A table
+    Table,
+}
+
+impl FieldKind {
+    /// Description used in `Error::InvalidValue`
+    fn expected(&self) -> &'static str {
+        match self {
+            FieldKind::String => "a string",
+            FieldKind::Integer => "an integer",
+            FieldKind::Float => "a number",
+            FieldKind::Bool => "a boolean",
+            FieldKind::Array => "an array",
+            FieldKind::Table => "a table",
+        }
+    }
+
+    /// The value as this kind, if it is one
+    fn check(&self, value: &Value) -> Option<Value> {
+        match (self, value) {
+            (FieldKind::String, Value::String(_))
+            | (FieldKind::Integer, Value::Integer(_))
+            | (FieldKind::Float, Value::Float(_))
+            | (FieldKind::Bool, Value::Boolean(_))
+            | (FieldKind::Array, Value::Array(_))
+            | (FieldKind::Table, Value::Table(_)) => Some(value.clone()),
+            (FieldKind::Float, Value::Integer(i)) => Some(Value::Float(*i as f64)),
+            _ => None,
+        }
+    }
+}
+
+impl Matches {
+    /// Collect a set of fields, checking that each is present and of the
+    /// given kind
+    ///
+    /// This is the typed-extraction path that needs no serde dependency.
+    /// Fields are looked up by canonical key (see `flat_map`), so a dotted
+    /// `toml_key` works, and then by arg name. A missing field fails with
+    /// `Error::MissingRequired`, one of the wrong kind with
+    /// `Error::InvalidValue`.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// # let matches = Matches::from_toml_str("[server]\nhost = \"h\"\nport = 80\n", "myapp")?;
+    /// let fields = matches.extract(&[
+    ///     ("server.host", FieldKind::String),
+    ///     ("server.port", FieldKind::Integer),
+    /// ])?;
+    /// # assert_eq!(fields["server.port"], Value::Integer(80));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extract(&self, fields: &[(&str, FieldKind)]) -> Result<HashMap<String, Value>> {
+        let flat = self.flat_map();
+        let mut extracted = HashMap::new();
+        for &(name, kind) in fields {
+            let value = flat
+                .get(name)
+                .or_else(|| self.values.get(name))
+                .ok_or_else(|| Error::MissingRequired {
+                    name: name.to_string(),
+                })?;
+            let value = kind.check(value).ok_or_else(|| Error::InvalidValue {
+                name: name.to_string(),
+                value: crate::raw_string(value),
+                expected: kind.expected(),
+                position: None,
+            })?;
+            extracted.insert(name.to_string(), value);
+        }
+        Ok(extracted)
+    }
+}
@@ -1,13 +1,39 @@
+mod completions;
+mod config;
+mod cycle;
+#[cfg(feature = "serde")]
+mod de;
+mod dotenv;
 mod error;
+mod event;
+mod extract;
+mod manpage;
 mod parser;
+mod prompt;
+mod spec;
+#[cfg(test)]
+mod tests;
+mod warning;
 use parser::ArgParser;
 
+pub use completions::Shell;
 pub use error::{Error, Result};
+pub use event::{InfoKind, ParseEvent};
+pub use extract::FieldKind;
 pub use stoml::{Array, Table, Value};
+pub use warning::Warning;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
-use std::path::Path;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum line width for help output before annotations wrap
+const HELP_WIDTH: usize = 80;
 
 /// The type of value an argument accepts
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -16,6 +42,8 @@ pub enum ArgType {
     String,
     /// An integer value
     Integer,
+    /// A non-negative integer value
+    UnsignedInteger,
     /// A floating-point value
     Float,
     /// A boolean flag (presence = true, --no-flag = false)
@@ -24,6 +52,81 @@ pub enum ArgType {
     Array,
     /// A count (each occurrence increments, e.g., -vvv = 3)
     Count,
+    /// A socket address ("127.0.0.1:8080", ":8080"), stored as a string
+    SocketAddr,
+    /// An IPv4 or IPv6 address, stored as a string
+    IpAddr,
+    /// Repeatable `key=value` pairs (`-D name=x`), stored as a table
+    KeyValue,
+    /// A filesystem path, stored as a string
+    Path,
+    /// A byte size ("512", "4K", "2MiB", "1GB"), stored as an integer
+    Bytes,
+    /// A duration ("30s", "500ms", "1h30m"), stored as integer milliseconds
+    Duration,
+}
+
+impl ArgType {
+    /// The TOML type name a value of this type is stored as
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ArgType::String | ArgType::SocketAddr | ArgType::IpAddr | ArgType::Path => "string",
+            ArgType::Integer
+            | ArgType::UnsignedInteger
+            | ArgType::Count
+            | ArgType::Bytes
+            | ArgType::Duration => "integer",
+            ArgType::Float => "float",
+            ArgType::Bool => "boolean",
+            ArgType::Array => "array",
+            ArgType::KeyValue => "table",
+        }
+    }
+}
+
+/// A shared callback stored on a builder
+struct Callback<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<callback>")
+    }
+}
+
+/// Where a resolved value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueSource {
+    /// Provided on the command line
+    Cli,
+    /// Typed in at an interactive prompt (see `Arg::prompt_if_missing`)
+    Prompt,
+    /// Read from an environment variable (see `Arg::env`)
+    Env,
+    /// Loaded from TOML configuration
+    Toml,
+    /// Implied by another argument (see `Arg::implies`)
+    Implied,
+    /// Filled in from the argument's default
+    Default,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Cli => write!(f, "cli"),
+            ValueSource::Prompt => write!(f, "prompt"),
+            ValueSource::Env => write!(f, "env"),
+            ValueSource::Toml => write!(f, "toml"),
+            ValueSource::Implied => write!(f, "implied"),
+            ValueSource::Default => write!(f, "default"),
+        }
+    }
 }
 
 /// Definition of a single argument
@@ -35,12 +138,16 @@ pub struct Arg {
     pub short: Option<char>,
     /// Long flag (e.g., "verbose" for --verbose)
     pub long: Option<String>,
+    /// Extra long flag spellings, hidden from help
+    pub aliases: Vec<String>,
     /// The type of value this argument accepts
     pub arg_type: ArgType,
     /// Default value if not provided
     pub default: Option<Value>,
     /// Whether this argument is required
     pub required: bool,
+    /// Args whose presence lifts the requirement (see `Arg::required_unless`)
+    pub required_unless: Vec<String>,
     /// Help description
     pub help: Option<String>,
     /// The TOML key path to look up (defaults to name)
@@ -48,14 +155,74 @@ pub struct Arg {
     pub toml_key: Option<String>,
     /// Value name shown in help (e.g., "FILE" in "--config <FILE>")
     pub value_name: Option<String>,
+    /// Per-value names shown in help for multi-value options
+    pub value_names: Vec<String>,
+    /// Number of values taken by each occurrence of the flag
+    pub num_values: usize,
+    /// Whether the flag takes all following values up to the next flag
+    pub multiple_values: bool,
+    /// Whether the flag joins all following tokens into one string value
+    pub rest_of_line: bool,
+    /// Most occurrences allowed for a Count flag
+    pub max_count: Option<i64>,
+    /// Inclusive bounds for numeric values (see `Arg::range`)
+    pub range: Option<(Value, Value)>,
+    /// Character splitting one array value into several
+    pub value_delimiter: Option<char>,
+    /// Whether repeated array elements are dropped, keeping the first
+    pub dedup: bool,
+    /// Character between key and value for `ArgType::KeyValue`
+    pub pair_separator: char,
+    /// Environment variable read when the argument is absent from the CLI
+    pub env: Option<String>,
     /// Whether this is a positional argument
     pub positional: bool,
     /// Position index for positional arguments
     pub position: Option<usize>,
     /// Whether this positional can accept multiple values (must be last)
     pub variadic: bool,
+    /// Allowed values (empty means any value is accepted)
+    pub possible_values: Vec<String>,
+    /// Whether to prompt for this argument on a TTY if it is required but missing
+    pub prompt: bool,
+    /// Whether prompted input should be hidden (e.g., passwords)
+    pub secret: bool,
+    /// Values this argument implies for other arguments when present
+    pub implies: Vec<(String, Value)>,
+    /// Conditional defaults as (other arg, value it must equal, default)
+    pub default_ifs: Vec<(String, Value, Value)>,
+    /// Members of a repeating positional group (empty if not a group)
+    pub group: Vec<Arg>,
+    /// Whether this argument is only accepted when experimental options are enabled
+    pub experimental: bool,
+    /// Text shown as the default in help instead of the actual default value
+    pub default_value_hint: Option<String>,
+    /// Port added to a bare IP for `ArgType::SocketAddr`
+    pub default_port: Option<u16>,
+    /// Whether an `ArgType::IpAddr` arg also accepts hostnames
+    pub allow_hostname: bool,
+    /// Deprecation message, warned about when the argument is used
+    pub deprecated: Option<String>,
+    /// Program version the argument was deprecated in
+    pub deprecated_since: Option<String>,
+    /// Program version from which using the argument is an error
+    pub removed_in: Option<String>,
+    /// Check run on every parsed value (see `Arg::validator`)
+    validator: Option<Callback<ValidatorFn>>,
+    /// Regex raw values must match (see `Arg::pattern`)
+    #[cfg(feature = "regex")]
+    pattern: Option<regex::Regex>,
 }
 
+/// An observer attached with `Args::on_parse_event`
+type EventFn = dyn Fn(ParseEvent) + Send + Sync;
+
+/// A value check attached with `Arg::validator`
+type ValidatorFn = dyn Fn(&Value) -> std::result::Result<(), String> + Send + Sync;
+
+/// A handler attached with `Args::on_unknown_config_key`
+type UnknownKeyFn = dyn Fn(&str, &Value);
+
 impl Arg {
     /// Create a new argument with the given name
     pub fn new(name: impl Into<String>) -> Self {
@@ -63,33 +230,51 @@ impl Arg {
             name: name.into(),
             short: None,
             long: None,
+            aliases: Vec::new(),
             arg_type: ArgType::String,
             default: None,
             required: false,
+            required_unless: Vec::new(),
             help: None,
             toml_key: None,
             value_name: None,
+            value_names: Vec::new(),
+            num_values: 1,
+            multiple_values: false,
+            rest_of_line: false,
+            max_count: None,
+            range: None,
+            value_delimiter: None,
+            dedup: false,
+            pair_separator: '=',
+            env: None,
             positional: false,
             position: None,
             variadic: false,
+            possible_values: Vec::new(),
+            prompt: false,
+            secret: false,
+            implies: Vec::new(),
+            default_ifs: Vec::new(),
+            group: Vec::new(),
+            experimental: false,
+            default_value_hint: None,
+            default_port: None,
+            allow_hostname: false,
+            deprecated: None,
+            deprecated_since: None,
+            removed_in: None,
+            validator: None,
+            #[cfg(feature = "regex")]
+            pattern: None,
         }
     }
 
     /// Create a new positional argument
     pub fn positional(name: impl Into<String>) -> Self {
         Arg {
-            name: name.into(),
-            short: None,
-            long: None,
-            arg_type: ArgType::String,
-            default: None,
-            required: false,
-            help: None,
-            toml_key: None,
-            value_name: None,
             positional: true,
-            position: None,
-            variadic: false,
+            ..Arg::new(name)
         }
     }
 
@@ -105,12 +290,34 @@ impl Arg {
         self
     }
 
+    /// Accept another long flag spelling (e.g. `--colour` for `--color`)
+    ///
+    /// Can be called repeatedly. Aliases are not shown in help.
+    pub fn alias(mut self, name: impl Into<String>) -> Self {
+        self.aliases.push(name.into());
+        self
+    }
+
     /// Set the argument type
     pub fn arg_type(mut self, t: ArgType) -> Self {
         self.arg_type = t;
         self
     }
 
+    /// Set the port used when an `ArgType::SocketAddr` value is a bare IP
+    pub fn default_port(mut self, port: u16) -> Self {
+        self.default_port = Some(port);
+        self
+    }
+
+    /// Let an `ArgType::IpAddr` arg accept hostnames as well as IPs
+    ///
+    /// By default IP args are strict and reject hostnames.
+    pub fn allow_hostname(mut self) -> Self {
+        self.allow_hostname = true;
+        self
+    }
+
     /// Convenience method for boolean flags
     pub fn flag(mut self) -> Self {
         self.arg_type = ArgType::Bool;
@@ -125,18 +332,99 @@ impl Arg {
         self
     }
 
+    /// Fail with `Error::CountExceeded` if a count flag is given more than
+    /// `n` times (short clusters and long repeats add up)
+    pub fn max_count(mut self, n: i64) -> Self {
+        self.max_count = Some(n);
+        self
+    }
+
+    /// Fail with `Error::OutOfRange` if an integer value is outside
+    /// `min..=max`
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let port = arg("port").long("port").arg_type(ArgType::Integer).range(1, 65535);
+    /// let result = args("myapp").arg(port).parse_from(vec!["--port=70000".into()]);
+    /// assert!(matches!(result, Err(Error::OutOfRange { .. })));
+    /// ```
+    pub fn range(mut self, min: i64, max: i64) -> Self {
+        self.range = Some((Value::Integer(min), Value::Integer(max)));
+        self
+    }
+
+    /// Like `range`, but with float bounds for `ArgType::Float` args
+    pub fn range_f64(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((Value::Float(min), Value::Float(max)));
+        self
+    }
+
     /// Set the default value
     pub fn default(mut self, v: impl Into<Value>) -> Self {
         self.default = Some(v.into());
         self
     }
 
+    /// Set the text shown as the default in help (e.g. `<hostname>`)
+    ///
+    /// Useful when the real default is computed at runtime and applied by the
+    /// application. The hint is shown instead of any static `default()`.
+    pub fn default_value_hint(mut self, s: &str) -> Self {
+        self.default_value_hint = Some(s.to_string());
+        self
+    }
+
+    /// Use `then` as the default when `other` has the value `eq`
+    ///
+    /// Can be called multiple times; clauses are checked in order and the
+    /// first match wins, falling back to the plain `default()`.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let threads = arg("threads")
+    ///     .arg_type(ArgType::Integer)
+    ///     .default(4i64)
+    ///     .default_if("mode", "safe", 1i64);
+    /// ```
+    pub fn default_if(
+        mut self,
+        other: impl Into<String>,
+        eq: impl Into<Value>,
+        then: impl Into<Value>,
+    ) -> Self {
+        self.default_ifs
+            .push((other.into(), eq.into(), then.into()));
+        self
+    }
+
     /// Mark this argument as required
+    ///
+    /// A required argument must be given on the command line or in the
+    /// config file. Its `default()` never satisfies the requirement.
     pub fn required(mut self) -> Self {
         self.required = true;
         self
     }
 
+    /// Require this argument unless `other` is present
+    ///
+    /// Checked after the config is merged, so `other` may come from the
+    /// config file. Call repeatedly to name several alternatives; any one of
+    /// them lifts the requirement.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let token = arg("token").long("token").required_unless("token-file");
+    /// ```
+    pub fn required_unless(mut self, other: impl Into<String>) -> Self {
+        self.required = true;
+        self.required_unless.push(other.into());
+        self
+    }
+
     /// Explicitly mark this argument as optional (this is the default)
     ///
     /// Use this for clarity when you want an optional argument with no default.
@@ -165,6 +453,322 @@ impl Arg {
         self
     }
 
+    /// Take `n` values per occurrence (e.g. `--point 1 2`)
+    ///
+    /// The values are stored as an array.
+    pub fn num_values(mut self, n: usize) -> Self {
+        self.num_values = n.max(1);
+        self
+    }
+
+    /// Take all following values up to the next flag (`--files a b c -v`)
+    ///
+    /// Values are collected into an array until a token that is a defined
+    /// flag, or `--`, is reached. Hyphenated tokens that aren't defined flags
+    /// (like `-5`) are taken as values. Repeating the flag appends.
+    pub fn multiple_values(mut self) -> Self {
+        self.multiple_values = true;
+        self
+    }
+
+    /// Take the rest of the command line as one string (`-m fix the bug`)
+    ///
+    /// All following tokens, flags included, are joined with spaces into a
+    /// single value. A `--` ends the value and keeps its usual meaning.
+    pub fn rest_of_line(mut self) -> Self {
+        self.rest_of_line = true;
+        self
+    }
+
+    /// Split each value of an array arg on `c` (`--tags a,b,c`)
+    ///
+    /// Splitting respects quotes and backslash escapes, so `a,"b,c",d`
+    /// yields `a`, `b,c` and `d`. Empty segments are skipped, and repeating
+    /// the flag keeps appending. Only array args are split.
+    pub fn value_delimiter(mut self, c: char) -> Self {
+        self.value_delimiter = Some(c);
+        self
+    }
+
+    /// Drop repeated values from an array or variadic arg
+    ///
+    /// `--feature x --feature y --feature x` collects `["x", "y"]`: the
+    /// first occurrence of each value is kept, in order.
+    pub fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
+
+    /// Set the key/value separator for `ArgType::KeyValue` (default `=`)
+    ///
+    /// Values are split on the first separator only, so with `:` the value
+    /// `Accept:text/html:q=1` gives key `Accept` and value `text/html:q=1`.
+    pub fn pair_separator(mut self, c: char) -> Self {
+        self.pair_separator = c;
+        self
+    }
+
+    /// Fall back to an environment variable when absent from the CLI
+    ///
+    /// The value is parsed like a CLI value; array values are split on the
+    /// arg's `value_delimiter` (a comma by default). Precedence is
+    /// CLI > env > TOML > default.
+    ///
+    /// If `var` is unset, an array arg also reads indexed variables `VAR_0`,
+    /// `VAR_1`, ... (one element each, not split), stopping at the first
+    /// missing index. `var` itself wins when both forms are set.
+    pub fn env(mut self, var: impl Into<String>) -> Self {
+        self.env = Some(var.into());
+        self
+    }
+
+    /// Name each value of a multi-value option, e.g. `--point <X> <Y>`
+    ///
+    /// Also sets `num_values` to the number of names.
+    pub fn value_names(mut self, names: &[&str]) -> Self {
+        self.value_names = names.iter().map(|s| s.to_string()).collect();
+        self.num_values = names.len().max(1);
+        self
+    }
+
+    /// Set the allowed values for this argument
+    ///
+    /// Any other value fails with `Error::InvalidChoice`.
+    pub fn possible_values(mut self, values: &[&str]) -> Self {
+        self.possible_values = values.iter().map(|v| v.to_string()).collect();
+        self
+    }
+
+    /// Prompt for this argument interactively if it is required but missing
+    ///
+    /// Prompting only happens when stdin is a terminal; otherwise the usual
+    /// missing argument error is returned.
+    pub fn prompt_if_missing(mut self) -> Self {
+        self.prompt = true;
+        self
+    }
+
+    /// Hide prompted input (implies `prompt_if_missing()`)
+    ///
+    /// If terminal echo can't be turned off (no `stty`, or not on Unix), the
+    /// prompt is refused with `Error::Io` instead of showing the input.
+    pub fn secret(mut self) -> Self {
+        self.prompt = true;
+        self.secret = true;
+        self
+    }
+
+    /// Make this positional a repeating group of records (must be last)
+    ///
+    /// Incoming values cycle through the members, each parsed with that
+    /// member's type, and every complete cycle becomes one record. A trailing
+    /// partial record is an error. Read the records with `Matches::get_records()`.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("env")
+    ///     .arg(pos("vars").positional_group(&[pos("key"), pos("value")]))
+    ///     .parse_from(vec!["A".into(), "1".into(), "B".into(), "2".into()])?;
+    /// // [{key = "A", value = "1"}, {key = "B", value = "2"}]
+    /// let vars = matches.get_records("vars");
+    /// assert_eq!(vars.len(), 2);
+    /// assert_eq!(vars[1].get("key"), Some(&Value::String("B".into())));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn positional_group(mut self, members: &[Arg]) -> Self {
+        self.group = members.to_vec();
+        self.variadic = true;
+        self.arg_type = ArgType::Array;
+        self
+    }
+
+    /// When this argument is present, set `other` to `value` unless it was
+    /// given on the command line (e.g. `--release` implies `--opt-level=3`)
+    ///
+    /// Can be called multiple times. If two present arguments imply different
+    /// values for the same target, the first one defined wins and a warning
+    /// is recorded.
+    pub fn implies(mut self, other: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.implies.push((other.into(), value.into()));
+        self
+    }
+
+    /// Mark this argument as experimental
+    ///
+    /// Experimental arguments are only accepted when `--enable-experimental`
+    /// is passed or the `<NAME>_ENABLE_EXPERIMENTAL` environment variable is
+    /// set (e.g. `MYAPP_ENABLE_EXPERIMENTAL=1`). Otherwise they are rejected as
+    /// unknown flags. They are hidden from `--help` and listed by `--help-all`.
+    pub fn experimental(mut self) -> Self {
+        self.experimental = true;
+        self
+    }
+
+    /// Value placeholder shown in help (e.g. "<FILE>"), if this arg takes a value
+    fn placeholder(&self) -> Option<String> {
+        if self.arg_type == ArgType::Bool || self.arg_type == ArgType::Count {
+            return None;
+        }
+        if !self.value_names.is_empty() {
+            let names: Vec<_> = self
+                .value_names
+                .iter()
+                .map(|n| format!("<{}>", n.to_uppercase()))
+                .collect();
+            return Some(names.join(" "));
+        }
+        if self.arg_type == ArgType::KeyValue && self.value_name.is_none() {
+            return Some(format!("<KEY{}VALUE>...", self.pair_separator));
+        }
+        let vname = match &self.value_name {
+            Some(v) => v.as_str(),
+            None if self.arg_type == ArgType::Path => "path",
+            None => &self.name,
+        };
+        let one = format!("<{}>", vname.to_uppercase());
+        if self.num_values > 1 {
+            Some(vec![one; self.num_values].join(" "))
+        } else if self.arg_type == ArgType::Array || self.multiple_values || self.rest_of_line {
+            Some(format!("{}...", one))
+        } else {
+            Some(one)
+        }
+    }
+
+    /// Mark this argument as deprecated
+    ///
+    /// Using it on the command line records a `Warning::Deprecated` with the
+    /// given message (e.g. "use --new instead").
+    pub fn deprecated(mut self, message: &str) -> Self {
+        self.deprecated = Some(message.to_string());
+        self
+    }
+
+    /// Mark this argument as deprecated since a program version
+    pub fn deprecated_since(mut self, version: &str, message: &str) -> Self {
+        self.deprecated = Some(message.to_string());
+        self.deprecated_since = Some(version.to_string());
+        self
+    }
+
+    /// Set the program version in which a deprecated argument is removed
+    ///
+    /// Once the program version (from `Args::version`) reaches it, using the
+    /// argument fails with `Error::RemovedArg` instead of warning.
+    pub fn removed_in(mut self, version: &str) -> Self {
+        self.removed_in = Some(version.to_string());
+        self
+    }
+
+    /// Reject parsed values the closure returns `Err` for
+    ///
+    /// The message ends up in `Error::Validation`.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let port = arg("port")
+    ///     .arg_type(ArgType::Integer)
+    ///     .validator(|v| match v.as_integer() {
+    ///         Some(1..=65535) => Ok(()),
+    ///         _ => Err("port must be between 1 and 65535".into()),
+    ///     });
+    /// ```
+    pub fn validator(
+        mut self,
+        f: impl Fn(&Value) -> std::result::Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.validator = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    /// Require values to match a regular expression
+    ///
+    /// Applies to the value as typed, and to each element of an array.
+    /// A mismatch fails with `Error::PatternMismatch`. Needs the `regex`
+    /// feature.
+    ///
+    /// # Panics
+    /// If `regex` is not a valid regular expression.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let tag = arg("tag").long("tag").pattern("^[a-z0-9-]+$");
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn pattern(mut self, regex: &str) -> Self {
+        let compiled = regex::Regex::new(regex)
+            .unwrap_or_else(|e| panic!("invalid pattern for argument '{}': {}", self.name, e));
+        self.pattern = Some(compiled);
+        self
+    }
+
+    /// Check a raw value against the allowed values and pattern, if any
+    fn check_raw(&self, value: &str) -> Result<()> {
+        if !self.possible_values.is_empty() && !self.possible_values.iter().any(|v| v == value) {
+            return Err(Error::InvalidChoice {
+                name: self.name.clone(),
+                value: value.to_string(),
+                allowed: self.possible_values.clone(),
+            });
+        }
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = &self.pattern
+            && !pattern.is_match(value)
+        {
+            return Err(Error::PatternMismatch {
+                name: self.name.clone(),
+                value: value.to_string(),
+                pattern: pattern.as_str().to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check a parsed value against the range, then run the validator
+    fn check_value(&self, value: &Value) -> Result<()> {
+        if let Some((min, max)) = &self.range
+            && !in_range(value, min, max)
+        {
+            return Err(Error::OutOfRange {
+                name: self.name.clone(),
+                value: raw_string(value),
+                min: min.to_string(),
+                max: max.to_string(),
+            });
+        }
+        match &self.validator {
+            Some(Callback(f)) => f(value).map_err(|message| Error::Validation {
+                name: self.name.clone(),
+                message,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Describe this argument's deprecation, e.g.
+    /// "deprecated since 2.0, will be removed in 3.0: use --new"
+    fn deprecation_note(&self) -> String {
+        let mut note = String::from("deprecated");
+        if let Some(since) = &self.deprecated_since {
+            note.push_str(&format!(" since {}", since));
+        }
+        if let Some(removed) = &self.removed_in {
+            if self.deprecated_since.is_some() {
+                note.push(',');
+            }
+            note.push_str(&format!(" will be removed in {}", removed));
+        }
+        if let Some(message) = self.deprecated.as_deref().filter(|m| !m.is_empty()) {
+            note.push_str(&format!(": {}", message));
+        }
+        note
+    }
+
     /// Mark this positional as variadic (accepts multiple values, must be last)
     pub fn variadic(mut self) -> Self {
         self.variadic = true;
@@ -194,10 +798,66 @@ pub struct Args {
     auto_config: bool,
     /// Default config file path (used if -c/--config not provided)
     default_config: Option<String>,
+    /// Environment variable naming the config file, checked before the default
+    config_env: Option<String>,
+    /// Config paths tried in order when no path is given explicitly
+    config_search_paths: Vec<String>,
     /// Default config content (written if config file doesn't exist)
     config_template: Option<String>,
     /// Whether config file is required
     config_required: bool,
+    /// Whether to list possible values in help output
+    show_possible_values: bool,
+    /// Whether unique prefixes of long flags are accepted
+    infer_long_args: bool,
+    /// Whether `--flag=value` is accepted
+    equals_syntax: bool,
+    /// Args whose value must be given as `--flag=value`
+    require_equals: Vec<String>,
+    /// Name to collect unknown long flags under, instead of erroring
+    capture_unknown: Option<String>,
+    /// Whether unrecognized subcommands are captured instead of erroring
+    external_subcommands: bool,
+    /// Whether unknown flags are passed through to `remaining`
+    ignore_unknown_flags: bool,
+    /// Experimental arguments set aside because they are not enabled
+    disabled_experimental: Vec<Arg>,
+    /// Observer for resolution milestones
+    on_event: Option<Callback<EventFn>>,
+    /// Handler for help/version output, replacing the error short-circuit
+    intercept_info: Option<Callback<dyn Fn(InfoKind, String) -> bool>>,
+    /// Callback for config keys that don't belong to any arg
+    on_unknown_config_key: Option<Callback<UnknownKeyFn>>,
+    /// Sets of args that must be given together or not at all
+    all_or_none: Vec<Vec<String>>,
+    /// Pairs of args that may not be given together
+    conflicts: Vec<(String, String)>,
+    /// (arg, needed arg) pairs: the first requires the second
+    requires: Vec<(String, String)>,
+    /// Extra report appended to `--version` output
+    version_details: Option<Callback<dyn Fn() -> String>>,
+    /// Path of a `.env` file to read variables from
+    dotenv_path: Option<String>,
+    /// Variables loaded from the `.env` file
+    dotenv_vars: HashMap<String, String>,
+    /// Child parsers dispatched to by name
+    subcommands: Vec<Args>,
+    /// Other names this parser answers to as a subcommand
+    aliases: Vec<String>,
+    /// Whether invalid values fall back to defaults with a warning
+    lenient: bool,
+    /// Whether `+x` turns off Bool short flags
+    plus_minus_toggles: bool,
+    /// Whether long flags and possible values are matched regardless of case
+    ignore_case: bool,
+    /// Fewest positional values accepted
+    min_positionals: usize,
+    /// Name shown in the usage line, if different from `name`
+    usage_name: Option<String>,
+    /// Status `parse_or_exit` uses for errors instead of `Error::exit_code`
+    error_exit_code: Option<i32>,
+    /// Originals of the argv tokens that weren't valid UTF-8, by index
+    os_args: HashMap<usize, OsString>,
 }
 
 impl Args {
@@ -213,8 +873,36 @@ impl Args {
             auto_version: true,
             auto_config: false,
             default_config: None,
+            config_env: None,
+            config_search_paths: Vec::new(),
             config_template: None,
             config_required: false,
+            show_possible_values: true,
+            infer_long_args: false,
+            equals_syntax: true,
+            require_equals: Vec::new(),
+            capture_unknown: None,
+            external_subcommands: false,
+            ignore_unknown_flags: false,
+            disabled_experimental: Vec::new(),
+            on_event: None,
+            intercept_info: None,
+            on_unknown_config_key: None,
+            all_or_none: Vec::new(),
+            conflicts: Vec::new(),
+            requires: Vec::new(),
+            version_details: None,
+            dotenv_path: None,
+            dotenv_vars: HashMap::new(),
+            subcommands: Vec::new(),
+            aliases: Vec::new(),
+            lenient: false,
+            plus_minus_toggles: false,
+            ignore_case: false,
+            min_positionals: 0,
+            usage_name: None,
+            error_exit_code: None,
+            os_args: HashMap::new(),
         }
     }
 
@@ -224,6 +912,27 @@ impl Args {
         self
     }
 
+    /// Append a detailed report to `--version` output
+    ///
+    /// The callback's output (e.g. linked library versions) is printed after
+    /// the `name version` line for `--version`. `-V` keeps the short form.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// let result = args("myapp")
+    ///     .version("1.0.0")
+    ///     .version_details(|| "stoml 0.1.0".to_string())
+    ///     .parse_from(vec!["--version".into()]);
+    /// let Err(Error::Version(text)) = result else { unreachable!() };
+    /// assert!(text.starts_with("myapp 1.0.0\n"));
+    /// assert!(text.contains("stoml 0.1.0"));
+    /// ```
+    pub fn version_details(mut self, f: impl Fn() -> String + 'static) -> Self {
+        self.version_details = Some(Callback(Arc::new(f)));
+        self
+    }
+
     /// Set the program description
     pub fn about(mut self, s: impl Into<String>) -> Self {
         self.about = Some(s.into());
@@ -252,72 +961,485 @@ impl Args {
         self
     }
 
-    /// Enable automatic config file flag (-c/--config)
+    /// Set whether help output lists each argument's possible values
     ///
-    /// This adds a `-c`/`--config` argument that is parsed first, before other arguments.
-    /// If provided, the TOML file is loaded and merged automatically.
+    /// Default is `true`.
+    pub fn show_possible_values(mut self, show: bool) -> Self {
+        self.show_possible_values = show;
+        self
+    }
+
+    /// Accept unique prefixes of long flags (e.g. `--verb` for `--verbose`)
     ///
-    /// # Example
-    /// ```ignore
-    /// let matches = args("myapp")
-    ///     .config_arg()
-    ///     .arg(arg("port").default(8080i64).toml_key("server.port"))
-    ///     .parse()?;
-    /// // If user runs: myapp -c config.toml
-    /// // The TOML is already loaded and merged
-    /// ```
-    pub fn config_arg(mut self) -> Self {
-        self.auto_config = true;
+    /// An exact match always takes priority over a prefix match. A prefix
+    /// that matches more than one flag fails with `Error::AmbiguousFlag`.
+    ///
+    /// With subcommands, flags before the subcommand name are resolved
+    /// against this parser's flags only, and flags after it against the
+    /// subcommand's only. A subcommand-local flag therefore always wins over
+    /// a parent flag sharing its prefix, and ambiguity is only possible
+    /// within one scope. Subcommand names themselves are never abbreviated.
+    pub fn infer_long_args(mut self, enabled: bool) -> Self {
+        self.infer_long_args = enabled;
         self
     }
 
-    /// Enable automatic config file flag with a default path
+    /// Only accept space-separated long flag values (`--port 8080`)
     ///
-    /// Like `config_arg()`, but also tries to load from the default path
-    /// if `-c`/`--config` is not provided.
+    /// With this set, `--port=8080` is read as a flag named `port=8080` and
+    /// fails with `Error::UnknownFlag`. This also applies to `--config=FILE`
+    /// and to boolean flags (`--verbose=false`); use `--no-verbose` instead.
+    /// Help output is unaffected, since it never shows the `=` form.
+    pub fn no_equals_syntax(mut self) -> Self {
+        self.equals_syntax = false;
+        self
+    }
+
+    /// Only accept `--flag=value` for the named args
     ///
-    /// # Example
-    /// ```ignore
-    /// let matches = args("myapp")
-    ///     .config_arg_default("config.toml")
-    ///     .parse()?;
-    /// // Loads config.toml if it exists, even without -c flag
-    /// ```
-    pub fn config_arg_default(mut self, path: impl Into<String>) -> Self {
-        self.auto_config = true;
-        self.default_config = Some(path.into());
+    /// For these args the next token is never taken as the value, so a value
+    /// can't be mistaken for a positional (or the other way around). The
+    /// space-separated form fails with `Error::RequiresEquals`. A short flag
+    /// needs its value attached (`-ofile`).
+    pub fn require_equals_for(mut self, names: &[&str]) -> Self {
+        self.require_equals
+            .extend(names.iter().map(|n| n.to_string()));
         self
     }
 
-    /// Set the default config content to write if config file doesn't exist
+    /// Collect unknown long flags into a map instead of erroring
     ///
-    /// When the resolved config path doesn't exist, this content will be
-    /// written to create the file before loading.
+    /// Each unknown `--key=value` or `--key value` is stored in a table under
+    /// `name`, readable with `Matches::get_map()`. Without `=`, the following
+    /// token is taken as the value unless it starts with `-`; if it does (or
+    /// there is none), the flag is stored as `true`. This means an unknown
+    /// boolean flag followed by a positional will swallow the positional, so
+    /// prefer the `=` form when forwarding values. Unknown short flags are
+    /// still rejected.
     ///
     /// # Example
-    /// ```ignore
-    /// const DEFAULT_CONFIG: &str = r#"
-    /// [server]
-    /// port = 8080
-    /// "#;
-    ///
-    /// let matches = args("myapp")
-    ///     .config_arg_default("config.toml")
-    ///     .config_template(DEFAULT_CONFIG)
-    ///     .parse()?;
-    /// // If config.toml doesn't exist, it's created with DEFAULT_CONFIG
     /// ```
-    pub fn config_template(mut self, content: impl Into<String>) -> Self {
-        self.config_template = Some(content.into());
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("proxy")
+    ///     .capture_unknown_into("backend")
+    ///     .parse_from(vec!["--timeout=30".into(), "--debug".into()])?;
+    /// let backend = matches.get_map("backend").unwrap();
+    /// assert_eq!(backend.get("timeout"), Some(&Value::String("30".into())));
+    /// assert_eq!(backend.get("debug"), Some(&Value::Boolean(true)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capture_unknown_into(mut self, name: &str) -> Self {
+        self.capture_unknown = Some(name.to_string());
         self
     }
 
-    /// Set whether the config file is required
+    /// Pass unknown flags through to `Matches::remaining()` instead of erroring
     ///
-    /// If `true` and no config file exists (and no template is provided),
-    /// parsing will fail with an error.
+    /// Unknown long flags are kept as given (`--foo=bar`). In a short flag
+    /// cluster, known flags are processed and each unknown char is kept as
+    /// its own flag, so `-vxq` with unknown `x` sets `v` and `q` and passes
+    /// through `-x`. Since the parser can't know whether an unknown flag takes
+    /// a value, a following value (`-x file`) is parsed as a positional, and a
+    /// value attached in a cluster (`-xfile`) is read as more flags.
+    pub fn ignore_unknown_flags(mut self) -> Self {
+        self.ignore_unknown_flags = true;
+        self
+    }
+
+    /// Capture an unrecognized first positional as an external subcommand
     ///
-    /// Default is `false` (config is optional).
+    /// Like git's `git foo` running `git-foo`, the first positional token and
+    /// every token after it (flags included) are stored untouched and can be
+    /// read with `Matches::external_subcommand()`, so a dispatcher can exec
+    /// `myapp-<name>`. Flags before the subcommand are parsed as usual. This
+    /// only applies when no positional arguments are defined.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .external_subcommands()
+    ///     .parse_from(vec!["deploy".into(), "--dry-run".into()])?;
+    /// if let Some((name, args)) = matches.external_subcommand() {
+    ///     assert_eq!(name, "deploy");
+    ///     let mut command = std::process::Command::new(format!("myapp-{}", name));
+    ///     command.args(args);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn external_subcommands(mut self) -> Self {
+        self.external_subcommands = true;
+        self
+    }
+
+    /// Require that the given args are either all present or all absent
+    ///
+    /// Presence is judged as for `conflicts` and `requires`: config values
+    /// count, defaults and `--no-flag` negations don't.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .arg(arg("cert").long("cert"))
+    ///     .arg(arg("key").long("key"))
+    ///     .all_or_none(&["cert", "key"])
+    ///     .parse()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_or_none(mut self, names: &[&str]) -> Self {
+        self.all_or_none
+            .push(names.iter().map(|n| n.to_string()).collect());
+        self
+    }
+
+    /// Forbid giving args `a` and `b` together
+    ///
+    /// Both being present fails with `Error::ConflictingArgs`. Only the
+    /// declared pair is checked; conflicts are not transitive.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .arg(arg("json").long("json").flag())
+    ///     .arg(arg("yaml").long("yaml").flag())
+    ///     .conflicts("json", "yaml")
+    ///     .parse()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn conflicts(mut self, a: &str, b: &str) -> Self {
+        self.conflicts.push((a.to_string(), b.to_string()));
+        self
+    }
+
+    /// Require `needs` to be present whenever `arg` is
+    ///
+    /// Checked after the config is merged, so either may come from the
+    /// config file. Missing it fails with `Error::MissingDependency`. Call
+    /// repeatedly to require several args.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .arg(arg("cert").long("cert"))
+    ///     .arg(arg("key").long("key"))
+    ///     .requires("cert", "key")
+    ///     .parse()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn requires(mut self, arg: &str, needs: &str) -> Self {
+        self.requires.push((arg.to_string(), needs.to_string()));
+        self
+    }
+
+    /// Replace invalid values with the arg's default instead of failing
+    ///
+    /// A value that can't be parsed as the arg's type (`Error::InvalidValue`),
+    /// is outside its `range` (`Error::OutOfRange`), or is rejected by its
+    /// validator (`Error::Validation`) is replaced by the arg's default, and
+    /// a `Warning::InvalidValue` is recorded. The replaced value's source is
+    /// `ValueSource::Default`. Args
+    /// without a default still fail, and all other errors (unknown flags,
+    /// missing values, missing required args, ...) are unaffected.
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Accept `+x` to turn off Bool short flag `-x` (tar/ps style)
+    ///
+    /// Clusters work too: `+xy` turns off both. A `+` token naming a
+    /// non-Bool or unknown flag fails with `Error::UnknownFlag`, so with this
+    /// enabled positionals can't start with `+`.
+    pub fn plus_minus_toggles(mut self) -> Self {
+        self.plus_minus_toggles = true;
+        self
+    }
+
+    /// Match long flags and possible values regardless of case
+    ///
+    /// `--Verbose` then resolves to `--verbose`, and `--color=Always` to the
+    /// possible value `always`, which is what gets stored. Short flags stay
+    /// case-sensitive, so `-v` and `-V` remain different flags. Long flags
+    /// that differ only by case are an `Error::InvalidDefinition`.
+    pub fn ignore_case(mut self, enabled: bool) -> Self {
+        self.ignore_case = enabled;
+        self
+    }
+
+    /// Require at least `n` positional values in total
+    ///
+    /// Every value counts, including each element of a variadic positional, so
+    /// `pos("files").variadic()` with `min_positionals(2)` means "at least two
+    /// files". Fewer fail with `Error::TooFewPositional`.
+    pub fn min_positionals(mut self, n: usize) -> Self {
+        self.min_positionals = n;
+        self
+    }
+
+    /// Add a subcommand
+    ///
+    /// When the first positional token is the subcommand's name, the remaining
+    /// tokens are parsed by `sub`. Flags given before the name belong to this
+    /// parser. Read the result with `Matches::subcommand()`.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .arg(arg("verbose").short('v').flag())
+    ///     .subcommand(args("server").arg(arg("port").long("port")))
+    ///     .parse_from(vec!["-v".into(), "server".into(), "--port".into(), "80".into()])?;
+    ///
+    /// if let Some(("server", sub)) = matches.subcommand() {
+    ///     println!("port: {:?}", sub.get_string("port"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subcommand(mut self, sub: Args) -> Self {
+        self.subcommands.push(sub);
+        self
+    }
+
+    /// Add another name this parser answers to as a subcommand
+    ///
+    /// The alias dispatches like the real name, but `Matches::subcommand()`
+    /// reports the real name. Aliases are not listed in help.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .subcommand(args("checkout").alias("co"))
+    ///     .parse_from(vec!["co".into()])?;
+    /// assert_eq!(matches.subcommand().map(|(name, _)| name), Some("checkout"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// Read environment variables from a `.env` file as well
+    ///
+    /// The file holds `KEY=value` lines (with `#` comments and optional
+    /// quotes) and is loaded at parse time into the parser's own map; the
+    /// process environment is never modified. Real environment variables
+    /// take precedence over the file. A missing file is ignored.
+    pub fn dotenv(mut self, path: &str) -> Self {
+        self.dotenv_path = Some(path.to_string());
+        self
+    }
+
+    /// Be told about config keys that don't belong to any declared arg
+    ///
+    /// The callback receives each such key, flattened to its dotted form
+    /// (`plugins.foo.enabled`), and its value. The keys are still merged into
+    /// the matches as usual.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .config_arg_default("config.toml")
+    ///     .on_unknown_config_key(|key, value| eprintln!("extra config: {} = {}", key, value))
+    ///     .parse()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_unknown_config_key(mut self, f: impl Fn(&str, &Value) + 'static) -> Self {
+        self.on_unknown_config_key = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    /// Observe resolution milestones as they happen
+    ///
+    /// The callback receives a `ParseEvent` for each config file created or
+    /// loaded, each value read from the environment, each prompted value, and
+    /// each implied value, in the order they are resolved. The returned
+    /// `Matches` keep the callback, so `Matches::with_defaults` reports each
+    /// default it applies. Useful for debugging complex precedence.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .config_arg_default("config.toml")
+    ///     .on_parse_event(|e| eprintln!("{:?}", e))
+    ///     .parse()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_parse_event(mut self, f: impl Fn(ParseEvent) + Send + Sync + 'static) -> Self {
+        self.on_event = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    /// Handle `--help` and `--version` output yourself
+    ///
+    /// The callback receives the kind of request and the formatted text, and
+    /// returns whether to continue parsing. If it returns false, parsing stops
+    /// with `Error::Help` or `Error::Version` as usual.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # struct Window;
+    /// # impl Window { fn show_text(&self, _: &str) {} }
+    /// # fn main() -> Result<()> {
+    /// # let window = Window;
+    /// let matches = args("myapp")
+    ///     .intercept_info(move |_, text| {
+    ///         window.show_text(&text);
+    ///         true
+    ///     })
+    ///     .parse_from(vec!["--help".into()])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn intercept_info(mut self, f: impl Fn(InfoKind, String) -> bool + 'static) -> Self {
+        self.intercept_info = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    /// Enable automatic config file flag (-c/--config)
+    ///
+    /// This adds a `-c`/`--config` argument that is parsed first, before other arguments.
+    /// If provided, the TOML file is loaded and merged automatically.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let matches = args("myapp")
+    ///     .config_arg()
+    ///     .arg(arg("port").default(8080i64).toml_key("server.port"))
+    ///     .parse()?;
+    /// // If user runs: myapp -c config.toml
+    /// // The TOML is already loaded and merged
+    /// ```
+    pub fn config_arg(mut self) -> Self {
+        self.auto_config = true;
+        self
+    }
+
+    /// Enable automatic config file flag with a default path
+    ///
+    /// Like `config_arg()`, but also tries to load from the default path
+    /// if `-c`/`--config` is not provided.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let matches = args("myapp")
+    ///     .config_arg_default("config.toml")
+    ///     .parse()?;
+    /// // Loads config.toml if it exists, even without -c flag
+    /// ```
+    pub fn config_arg_default(mut self, path: impl Into<String>) -> Self {
+        self.auto_config = true;
+        self.default_config = Some(path.into());
+        self
+    }
+
+    /// Read the config path from an environment variable
+    ///
+    /// Enables the config flag like `config_arg()`. The variable is used when
+    /// `-c`/`--config` is not given, and takes precedence over the default
+    /// path. Like `-c`, a path from the variable is never created from the
+    /// template.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .config_arg_default("config.toml")
+    ///     .config_env("MYAPP_CONFIG")
+    ///     .parse()?;
+    /// // MYAPP_CONFIG=/etc/myapp.toml myapp  loads /etc/myapp.toml
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn config_env(mut self, var: impl Into<String>) -> Self {
+        self.auto_config = true;
+        self.config_env = Some(var.into());
+        self
+    }
+
+    /// Look for the config file in several places, most specific first
+    ///
+    /// Enables the config flag like `config_arg()`. When no path is given
+    /// with `-c`/`--config` or `config_env`, the first path that exists is
+    /// loaded, and only that one. If none exists, the default path is used
+    /// as usual. A leading `~/` is expanded from `HOME`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let matches = args("myapp")
+    ///     .config_search_paths(vec![
+    ///         "./myapp.toml".into(),
+    ///         "~/.config/myapp/config.toml".into(),
+    ///         "/etc/myapp/config.toml".into(),
+    ///     ])
+    ///     .parse()?;
+    /// println!("using {:?}", matches.config_path());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn config_search_paths(mut self, paths: Vec<String>) -> Self {
+        self.auto_config = true;
+        self.config_search_paths = paths;
+        self
+    }
+
+    /// Set the default config content to write if config file doesn't exist
+    ///
+    /// When the default config path doesn't exist, this content will be
+    /// written to create the file before loading. A path given explicitly
+    /// with `-c` is never created.
+    ///
+    /// # Example
+    /// ```ignore
+    /// const DEFAULT_CONFIG: &str = r#"
+    /// [server]
+    /// port = 8080
+    /// "#;
+    ///
+    /// let matches = args("myapp")
+    ///     .config_arg_default("config.toml")
+    ///     .config_template(DEFAULT_CONFIG)
+    ///     .parse()?;
+    /// // If config.toml doesn't exist, it's created with DEFAULT_CONFIG
+    /// ```
+    pub fn config_template(mut self, content: impl Into<String>) -> Self {
+        self.config_template = Some(content.into());
+        self
+    }
+
+    /// Set whether the config file is required
+    ///
+    /// If `true` and no config file exists (and no template is provided),
+    /// parsing will fail with an error.
+    ///
+    /// Default is `false` (config is optional).
     ///
     /// # Example
     /// ```ignore
@@ -331,19 +1453,119 @@ impl Args {
         self
     }
 
+    /// Check the argument definitions for mistakes
+    ///
+    /// Reports duplicate names, flags and TOML keys, clashes with the
+    /// automatic flags, badly ordered positionals, required arguments that
+    /// also have a default, cycles among implications and conditional
+    /// defaults, and invalid flag characters. All problems are
+    /// collected into a single `Error::InvalidDefinitions`.
+    pub fn validate(&self) -> Result<()> {
+        let reasons = self.definition_problems();
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidDefinitions { reasons })
+        }
+    }
+
+    /// Panic if the argument definitions are invalid
+    ///
+    /// Meant to be called from a unit test so a broken CLI definition fails
+    /// the build rather than a user's invocation.
+    ///
+    /// # Example
+    /// ```
+    /// # use stoml_args::*;
+    /// # fn build_cli() -> Args { args("myapp") }
+    /// // In a unit test of the application:
+    /// build_cli().assert_valid();
+    /// ```
+    pub fn assert_valid(&self) {
+        if let Err(e) = self.validate() {
+            panic!("{}", e);
+        }
+    }
+
     /// Parse arguments from the command line
     pub fn parse(self) -> Result<Matches> {
-        self.parse_from(env::args().skip(1).collect())
+        self.parse_from_os(env::args_os().skip(1).collect())
+    }
+
+    /// Exit with a fixed status for every error, instead of the sysexits.h
+    /// mapping of `Error::exit_code`
+    ///
+    /// Used by `parse_or_exit`. Help and version requests still exit with 0.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// let matches = args("myapp").error_exit_code(2).parse_or_exit();
+    /// ```
+    pub fn error_exit_code(mut self, code: i32) -> Self {
+        self.error_exit_code = Some(code);
+        self
+    }
+
+    /// Parse arguments from the command line, exiting on help, version or
+    /// an error
+    ///
+    /// The status comes from `error_exit_code` if set, otherwise from
+    /// `Error::exit_code`.
+    pub fn parse_or_exit(self) -> Matches {
+        let error_exit_code = self.error_exit_code;
+        self.parse().unwrap_or_else(|e| {
+            let code = exit_status(&e, error_exit_code);
+            e.exit_with(code)
+        })
+    }
+
+    /// Parse arguments that may not be valid UTF-8
+    ///
+    /// Flags are matched as UTF-8. A value that isn't valid UTF-8 is stored
+    /// lossily as a string, and its original bytes are kept for
+    /// `Matches::get_os` and `Matches::get_path`, including values attached
+    /// with `--flag=value`. Array elements keep theirs for
+    /// `Matches::get_os_vec`.
+    pub fn parse_from_os(mut self, args: Vec<OsString>) -> Result<Matches> {
+        let args = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, arg)| {
+                arg.into_string().unwrap_or_else(|os| {
+                    let lossy = os.to_string_lossy().into_owned();
+                    self.os_args.insert(i, os);
+                    lossy
+                })
+            })
+            .collect();
+        self.parse_from(args)
     }
 
     /// Parse arguments from a given iterator
+    ///
+    /// If `STOML_ARGS_DEBUG` is set, the resolved values are printed to stderr
+    /// with their sources before returning.
     pub fn parse_from(mut self, args: Vec<String>) -> Result<Matches> {
+        self.check_flag_names()?;
+
+        if let Some(path) = &self.dotenv_path {
+            self.dotenv_vars = dotenv::load(Path::new(path))?;
+        }
+
         // Pre-scan for config file if auto_config is enabled
-        let config_table = if self.auto_config {
-            let config_path = self.extract_config_path(&args);
-            self.load_or_create_config(config_path.as_deref())?
+        let (config_path, config_table) = if self.auto_config {
+            let (path, is_default) = match self.extract_config_path(&args) {
+                Some(path) => (Some(path), false),
+                None => match self.search_config() {
+                    Some(path) => (Some(path), false),
+                    None => (self.default_config.clone(), true),
+                },
+            };
+            let table = self.load_or_create_config(path.as_deref(), is_default)?;
+            (path.filter(|_| table.is_some()), table)
         } else {
-            None
+            (None, None)
         };
 
         // Add auto flags
@@ -374,26 +1596,142 @@ impl Args {
                     .help("Print version information"),
             );
         }
+        if self.args.iter().any(|a| a.experimental) {
+            self.args.push(
+                Arg::new("enable-experimental")
+                    .long("enable-experimental")
+                    .flag()
+                    .experimental()
+                    .help("Accept experimental options"),
+            );
+            if self.auto_help {
+                self.args.push(
+                    Arg::new("help-all")
+                        .long("help-all")
+                        .flag()
+                        .help("Print help including experimental options"),
+                );
+            }
+            // Set disabled experimental args aside so the parser rejects them
+            if !self.experimental_enabled(&args) {
+                let (disabled, enabled) = std::mem::take(&mut self.args)
+                    .into_iter()
+                    .partition(|a| a.experimental);
+                self.args = enabled;
+                self.disabled_experimental = disabled;
+            }
+        }
 
-        let parser = ArgParser::new(&self.args);
+        let os_args = std::mem::take(&mut self.os_args);
+        let parser = ArgParser::new(&self.args)
+            .infer_long_args(self.infer_long_args)
+            .equals_syntax(self.equals_syntax)
+            .require_equals(self.require_equals.clone())
+            .capture_unknown_into(self.capture_unknown.clone())
+            .external_subcommands(self.external_subcommands)
+            .ignore_unknown_flags(self.ignore_unknown_flags)
+            .subcommands(
+                self.subcommands
+                    .iter()
+                    .map(|s| (s.name.clone(), s.aliases.clone()))
+                    .collect(),
+            )
+            .lenient(self.lenient)
+            .plus_minus_toggles(self.plus_minus_toggles)
+            .ignore_case(self.ignore_case)
+            .os_args(os_args.clone());
+        // `-V` gives the short form, any spelling of `--version` the long one
+        let long_version = args
+            .iter()
+            .take_while(|a| *a != "--")
+            .any(|a| parser.is_long_flag_for(a, "version"));
+        let arg_count = args.len();
         let mut matches = parser.parse(args)?;
 
         // Check for help/version
-        if self.auto_help && matches.get_bool("help") {
-            return Err(Error::Help(self.format_help()));
+        let info = if self.auto_help && matches.get_bool("help") {
+            Some((InfoKind::Help, self.format_help(false)))
+        } else if self.auto_help && matches.get_bool("help-all") {
+            Some((InfoKind::Help, self.format_help(true)))
+        } else if self.auto_version && matches.get_bool("version") {
+            Some((InfoKind::Version, self.format_version(long_version)))
+        } else {
+            None
+        };
+        if let Some((kind, text)) = info {
+            let proceed = match &self.intercept_info {
+                Some(Callback(f)) => f(kind, text.clone()),
+                None => false,
+            };
+            if !proceed {
+                return Err(match kind {
+                    InfoKind::Help => Error::Help(text),
+                    InfoKind::Version => Error::Version(text),
+                });
+            }
         }
-        if self.auto_version && matches.get_bool("version") {
-            return Err(Error::Version(self.format_version()));
+
+        // Fill in absent args from their environment variables
+        for (idx, arg) in self.args.iter().enumerate() {
+            let Some(var) = &arg.env else {
+                continue;
+            };
+            if matches.values.contains_key(&arg.name) {
+                let env_set = self.env_var(var).is_some()
+                    || (arg.arg_type == ArgType::Array
+                        && !indexed_env_values(var, |v| self.env_var(v)).is_empty());
+                if env_set {
+                    matches.note_overridden(&arg.name);
+                }
+                continue;
+            }
+            if let Some(value) = self.env_var(var) {
+                parser.set_env_value(idx, &value, &mut matches)?;
+                self.emit(ParseEvent::EnvResolved { var: var.clone() });
+            } else if arg.arg_type == ArgType::Array {
+                let elements = indexed_env_values(var, |v| self.env_var(v));
+                if !elements.is_empty() {
+                    parser.set_env_elements(idx, &elements, &mut matches)?;
+                    for i in 0..elements.len() {
+                        self.emit(ParseEvent::EnvResolved {
+                            var: format!("{}_{}", var, i),
+                        });
+                    }
+                }
+            }
         }
 
         // Merge TOML config (CLI values take precedence since they're already in matches)
         if let Some(table) = config_table {
+            if let Some(Callback(f)) = &self.on_unknown_config_key {
+                self.report_unknown_keys(&table, "", f.as_ref());
+            }
+            self.reconcile_config(&table, &mut matches);
             matches.merge_toml(&table, "");
+            matches.coerce_bools(&self.args);
         }
 
+        self.check_deprecations(&mut matches)?;
+        self.apply_implications(&mut matches);
+
         // Check for missing required arguments (after help/version and TOML merge)
-        for arg in &self.args {
-            if arg.required && !matches.values.contains_key(&arg.name) {
+        for (idx, arg) in self.args.iter().enumerate() {
+            if self.is_missing(arg, &matches) {
+                // Ask for the value interactively if possible
+                if arg.prompt && prompt::is_interactive() {
+                    let input = prompt::read_line(&format!("{}: ", arg.name), arg.secret)?;
+                    if !input.is_empty() {
+                        parser.set_value(idx, &input, &mut matches)?;
+                        matches
+                            .sources
+                            .insert(arg.name.clone(), ValueSource::Prompt);
+                        self.emit(ParseEvent::Prompted {
+                            name: arg.name.clone(),
+                        });
+                        continue;
+                    }
+                }
+
                 if arg.positional {
                     return Err(Error::MissingPositional {
                         name: arg.name.clone(),
@@ -407,19 +1745,370 @@ impl Args {
             }
         }
 
+        if matches.positional_count < self.min_positionals {
+            return Err(Error::TooFewPositional {
+                min: self.min_positionals,
+                got: matches.positional_count,
+            });
+        }
+
+        // Check all-or-none groups
+        for group in &self.all_or_none {
+            let (present, missing): (Vec<String>, Vec<String>) = group
+                .iter()
+                .cloned()
+                .partition(|name| self.is_present(name, &matches));
+            if !present.is_empty() && !missing.is_empty() {
+                return Err(Error::IncompleteGroup { present, missing });
+            }
+        }
+
+        // Check conflicting pairs
+        for (a, b) in &self.conflicts {
+            if self.is_present(a, &matches) && self.is_present(b, &matches) {
+                return Err(Error::ConflictingArgs {
+                    names: vec![a.clone(), b.clone()],
+                });
+            }
+        }
+
+        // Check dependencies (config values count, by name or TOML key)
+        for (arg, needs) in &self.requires {
+            if self.is_present(arg, &matches) && !self.is_present(needs, &matches) {
+                return Err(Error::MissingDependency {
+                    arg: arg.clone(),
+                    needs: needs.clone(),
+                });
+            }
+        }
+
+        // Dump the resolved config for operators debugging a deployment
+        if env::var_os(DEBUG_CONFIG_ENV).is_some() {
+            let usage = self.usage_name.as_deref().unwrap_or(&self.name);
+            let resolved = matches.clone().with_defaults(&self.args);
+            eprint!("# resolved config for {}\n{}", usage, resolved.debug_tree());
+        }
+
+        // Dispatch to the chosen subcommand
+        if let Some((name, rest)) = matches.pending_subcommand.take()
+            && let Some(pos) = self.subcommands.iter().position(|s| s.name == name)
+        {
+            let mut sub = self.subcommands.swap_remove(pos);
+            let usage = self.usage_name.as_deref().unwrap_or(&self.name);
+            sub.usage_name = Some(format!("{} {}", usage, sub.name));
+            // Error positions count from the start of the parent's arguments
+            let offset = arg_count - rest.len();
+            sub.os_args = os_args
+                .into_iter()
+                .filter_map(|(i, os)| Some((i.checked_sub(offset)?, os)))
+                .collect();
+            let sub_matches = sub
+                .parse_from(rest)
+                .map_err(|e| e.offset_position(offset))?;
+            matches.subcommand = Some((name, Box::new(sub_matches)));
+        }
+
         // Store metadata
+        matches.record_keys(&self.args);
         matches.program_name = self.name;
+        matches.on_event = self.on_event;
+        matches.config_path = config_path;
 
         Ok(matches)
     }
 
-    /// Extract config path from args without full parsing
+    /// List the required args that `matches` lacks, without failing
+    ///
+    /// Useful for UIs that prompt for or display missing values instead of
+    /// erroring. Empty when all requirements are met.
+    pub fn missing_required(&self, matches: &Matches) -> Vec<String> {
+        self.args
+            .iter()
+            .filter(|a| self.is_missing(a, matches))
+            .map(|a| a.name.clone())
+            .collect()
+    }
+
+    /// Whether a required arg has no value in `matches`
+    fn is_missing(&self, arg: &Arg, matches: &Matches) -> bool {
+        arg.required
+            && !matches.is_supplied(arg)
+            && !arg
+                .required_unless
+                .iter()
+                .any(|other| self.is_present(other, matches))
+    }
+
+    /// Whether `name` was given, by arg name or TOML key; defaults and
+    /// explicit `false` negations (`--no-json`) don't count
+    fn is_present(&self, name: &str, matches: &Matches) -> bool {
+        let is_set = |key: &str| {
+            matches
+                .values
+                .get(key)
+                .is_some_and(|v| *v != Value::Boolean(false))
+                && matches.sources.get(key) != Some(&ValueSource::Default)
+        };
+        match self.args.iter().find(|a| a.name == name) {
+            Some(arg) => is_set(&arg.name) || arg.toml_key.as_deref().is_some_and(is_set),
+            None => is_set(name),
+        }
+    }
+
+    /// Reject flag names that could never be typed or parsed
+    fn check_flag_names(&self) -> Result<()> {
+        match self.flag_name_problems().into_iter().next() {
+            Some(reason) => Err(Error::InvalidDefinition { reason }),
+            None => Ok(()),
+        }
+    }
+
+    /// Flags containing characters the parser cannot match
+    fn flag_name_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for arg in &self.args {
+            if let Some(c) = arg.short
+                && (c.is_control() || c.is_whitespace() || c == '-' || c == '=')
+            {
+                problems.push(format!(
+                    "argument '{}' has invalid short flag {:?}",
+                    arg.name, c
+                ));
+            }
+            for long in arg.long.iter().chain(&arg.aliases) {
+                if long.is_empty() || long.contains(|c: char| c == '=' || c.is_whitespace()) {
+                    problems.push(format!(
+                        "argument '{}' has invalid long flag {:?}",
+                        arg.name, long
+                    ));
+                }
+            }
+        }
+        // Flags differing only by case would resolve to whichever came last
+        if self.ignore_case {
+            let mut folded: HashMap<String, (&str, &str)> = HashMap::new();
+            for arg in &self.args {
+                for long in arg.long.iter().chain(&arg.aliases) {
+                    let key = long.to_lowercase();
+                    if let Some((name, other)) = folded.insert(key, (&arg.name, long))
+                        && name != arg.name
+                        && other != long
+                    {
+                        problems.push(format!(
+                            "long flags --{} and --{} differ only by case",
+                            other, long
+                        ));
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    /// Collect every problem with the argument definitions
+    fn definition_problems(&self) -> Vec<String> {
+        let mut problems = self.flag_name_problems();
+
+        // Flags the auto-added arguments will claim
+        let mut shorts: HashMap<char, String> = HashMap::new();
+        let mut longs: HashMap<String, String> = HashMap::new();
+        if self.auto_config {
+            shorts.insert('c', "config".to_string());
+            longs.insert("config".to_string(), "config".to_string());
+        }
+        if self.auto_help {
+            shorts.insert('h', "help".to_string());
+            longs.insert("help".to_string(), "help".to_string());
+        }
+        if self.auto_version && self.version.is_some() {
+            shorts.insert('V', "version".to_string());
+            longs.insert("version".to_string(), "version".to_string());
+        }
+
+        let mut subcommand_names: HashSet<&str> = HashSet::new();
+        for sub in &self.subcommands {
+            if !subcommand_names.insert(&sub.name) {
+                problems.push(format!(
+                    "subcommand '{}' is defined more than once",
+                    sub.name
+                ));
+            }
+            for alias in &sub.aliases {
+                if !subcommand_names.insert(alias) {
+                    problems.push(format!(
+                        "subcommand alias '{}' is already a subcommand name or alias",
+                        alias
+                    ));
+                }
+            }
+            for problem in sub.definition_problems() {
+                problems.push(format!("subcommand '{}': {}", sub.name, problem));
+            }
+        }
+
+        let mut names: HashSet<&str> = HashSet::new();
+        let mut toml_keys: HashMap<&str, &str> = HashMap::new();
+        for arg in &self.args {
+            if !names.insert(&arg.name) {
+                problems.push(format!(
+                    "argument name '{}' is defined more than once",
+                    arg.name
+                ));
+            }
+            if let Some(c) = arg.short
+                && let Some(other) = shorts.insert(c, arg.name.clone())
+            {
+                problems.push(format!(
+                    "short flag '-{}' is used by both '{}' and '{}'",
+                    c, other, arg.name
+                ));
+            }
+            for long in arg.long.iter().chain(&arg.aliases) {
+                let key = if self.ignore_case {
+                    long.to_lowercase()
+                } else {
+                    long.clone()
+                };
+                if let Some(other) = longs.insert(key, arg.name.clone()) {
+                    problems.push(format!(
+                        "long flag '--{}' is used by both '{}' and '{}'",
+                        long, other, arg.name
+                    ));
+                }
+            }
+            if let Some(key) = &arg.toml_key
+                && let Some(other) = toml_keys.insert(key, &arg.name)
+            {
+                problems.push(format!(
+                    "TOML key '{}' is used by both '{}' and '{}'",
+                    key, other, arg.name
+                ));
+            }
+            if arg.required && arg.default.is_some() {
+                problems.push(format!(
+                    "argument '{}' is required but also has a default value",
+                    arg.name
+                ));
+            }
+            if arg.variadic && !arg.positional {
+                problems.push(format!(
+                    "argument '{}' is variadic but not positional",
+                    arg.name
+                ));
+            }
+            if let Some((min, max)) = &arg.range
+                && !in_range(max, min, max)
+            {
+                problems.push(format!(
+                    "argument '{}' has range {}..={}, which is empty",
+                    arg.name, min, max
+                ));
+            }
+        }
+
+        // Implications and conditional defaults must not depend on themselves
+        let mut implies: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut default_ifs: HashMap<&str, Vec<&str>> = HashMap::new();
+        for arg in &self.args {
+            for (target, _) in &arg.implies {
+                implies.entry(&arg.name).or_default().push(target);
+            }
+            for (other, _, _) in &arg.default_ifs {
+                default_ifs.entry(&arg.name).or_default().push(other);
+            }
+        }
+        if let Some(cycle) = cycle::find_cycle(&implies) {
+            problems.push(format!("implication cycle: {}", cycle.join(" -> ")));
+        }
+        if let Some(cycle) = cycle::find_cycle(&default_ifs) {
+            problems.push(format!("conditional default cycle: {}", cycle.join(" -> ")));
+        }
+
+        let positionals: Vec<_> = self.args.iter().filter(|a| a.positional).collect();
+        let mut optional_seen: Option<&str> = None;
+        for (idx, arg) in positionals.iter().enumerate() {
+            if arg.variadic && idx + 1 < positionals.len() {
+                problems.push(format!(
+                    "variadic positional '{}' must be the last positional",
+                    arg.name
+                ));
+            }
+            if arg.required {
+                if let Some(optional) = optional_seen {
+                    problems.push(format!(
+                        "required positional '{}' follows optional positional '{}'",
+                        arg.name, optional
+                    ));
+                }
+            } else if optional_seen.is_none() {
+                optional_seen = Some(&arg.name);
+            }
+        }
+        if !self.equals_syntax && !self.require_equals.is_empty() {
+            problems.push(format!(
+                "'{}' require the '=' form, but no_equals_syntax is set",
+                self.require_equals.join("', '")
+            ));
+        }
+        if !positionals.iter().any(|a| a.variadic) && self.min_positionals > positionals.len() {
+            problems.push(format!(
+                "min_positionals {} exceeds the {} defined positionals",
+                self.min_positionals,
+                positionals.len()
+            ));
+        }
+
+        problems
+    }
+
+    /// Look up an environment variable, falling back to the `.env` file
+    fn env_var(&self, name: &str) -> Option<String> {
+        env::var(name)
+            .ok()
+            .or_else(|| self.dotenv_vars.get(name).cloned())
+    }
+
+    /// Whether experimental args are enabled via flag or environment variable
+    fn experimental_enabled(&self, args: &[String]) -> bool {
+        let var = format!(
+            "{}_ENABLE_EXPERIMENTAL",
+            self.name.to_uppercase().replace('-', "_")
+        );
+        let from_env = self
+            .env_var(&var)
+            .and_then(|v| parse_bool(&v))
+            .unwrap_or(false);
+
+        from_env
+            || args
+                .iter()
+                .take_while(|a| *a != "--")
+                .any(|a| a == "--enable-experimental")
+    }
+
+    /// Extract an explicitly given config path from args without full parsing
+    ///
+    /// Falls back to the `config_env` variable, if set and not empty.
     fn extract_config_path(&self, args: &[String]) -> Option<String> {
         let mut iter = args.iter().peekable();
         while let Some(arg) = iter.next() {
+            // Tokens after `--` or a subcommand name aren't ours
+            if arg == "--"
+                || self
+                    .subcommands
+                    .iter()
+                    .any(|s| s.name == *arg || s.aliases.contains(arg))
+            {
+                break;
+            }
             // --config=path or --config path
-            if let Some(rest) = arg.strip_prefix("--config") {
-                if let Some(path) = rest.strip_prefix('=') {
+            let long = arg.get(..8).filter(|flag| {
+                *flag == "--config" || (self.ignore_case && flag.eq_ignore_ascii_case("--config"))
+            });
+            if let Some(rest) = long.map(|_| &arg[8..]) {
+                if let Some(path) = rest.strip_prefix('=')
+                    && self.equals_syntax
+                {
                     return Some(path.to_string());
                 } else if rest.is_empty() {
                     return iter.next().cloned();
@@ -449,23 +2138,189 @@ impl Args {
                 }
             }
         }
-        // Fall back to default config path
-        self.default_config.clone()
+        self.config_env
+            .as_deref()
+            .and_then(|var| self.env_var(var))
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Note which config values were overridden on the command line, and record
+    /// a warning for every config value whose type disagrees with the CLI value
+    /// for the same arg, or with the arg's declared type
+    fn reconcile_config(&self, table: &Table, matches: &mut Matches) {
+        for arg in &self.args {
+            let key = arg.toml_key.as_deref().unwrap_or(&arg.name);
+            let Some(config_value) = lookup_toml(table, key) else {
+                continue;
+            };
+
+            let expected = match matches.values.get(&arg.name) {
+                Some(cli_value) => {
+                    let expected = cli_value.type_name();
+                    matches.note_overridden(&arg.name);
+                    expected
+                }
+                None => arg.arg_type.type_name(),
+            };
+
+            let coercible =
+                expected == "boolean" && config_value.as_str().and_then(parse_bool).is_some();
+            if config_value.type_name() != expected && !coercible {
+                matches.warnings.push(Warning::TypeMismatch {
+                    key: key.to_string(),
+                    arg: arg.name.clone(),
+                    expected,
+                    got: config_value.type_name(),
+                });
+            }
+        }
+    }
+
+    /// Warn about deprecated args used on the command line, or fail if the
+    /// program version has reached their removal version
+    fn check_deprecations(&self, matches: &mut Matches) -> Result<()> {
+        for arg in &self.args {
+            if arg.deprecated.is_none() && arg.removed_in.is_none() {
+                continue;
+            }
+            if matches.sources.get(&arg.name) != Some(&ValueSource::Cli) {
+                continue;
+            }
+            if let (Some(removed), Some(current)) = (&arg.removed_in, &self.version)
+                && version_at_least(current, removed)
+            {
+                return Err(Error::RemovedArg {
+                    name: arg.name.clone(),
+                    version: removed.clone(),
+                });
+            }
+            matches.warnings.push(Warning::Deprecated {
+                arg: arg.name.clone(),
+                message: arg.deprecation_note(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Set values implied by present arguments, never overriding CLI values
+    fn apply_implications(&self, matches: &mut Matches) {
+        let mut applied: HashMap<String, (String, Value)> = HashMap::new();
+
+        for arg in &self.args {
+            if arg.implies.is_empty() {
+                continue;
+            }
+            // A flag that is off (or never counted) implies nothing
+            match (matches.values.get(&arg.name), arg.arg_type) {
+                (None, _) | (Some(Value::Boolean(false)), _) => continue,
+                (Some(Value::Integer(0)), ArgType::Count) => continue,
+                _ => {}
+            }
+
+            for (target, value) in &arg.implies {
+                if matches!(
+                    matches.sources.get(target),
+                    Some(ValueSource::Cli | ValueSource::Env)
+                ) {
+                    continue;
+                }
+                if let Some((first, previous)) = applied.get(target) {
+                    if previous != value {
+                        matches.warnings.push(Warning::ConflictingImplication {
+                            target: target.clone(),
+                            first: first.clone(),
+                            second: arg.name.clone(),
+                        });
+                    }
+                    continue;
+                }
+                matches.values.insert(target.clone(), value.clone());
+                matches.sources.insert(target.clone(), ValueSource::Implied);
+                self.emit(ParseEvent::Implied {
+                    name: target.clone(),
+                    by: arg.name.clone(),
+                });
+                applied.insert(target.clone(), (arg.name.clone(), value.clone()));
+            }
+        }
+    }
+
+    /// Call `f` for every config leaf that isn't covered by a declared arg
+    fn report_unknown_keys(&self, table: &Table, prefix: &str, f: &dyn Fn(&str, &Value)) {
+        for (key, value) in table.iter() {
+            let full_key = if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+
+            let declared = self
+                .args
+                .iter()
+                .chain(&self.disabled_experimental)
+                .any(|a| {
+                    let arg_key = a.toml_key.as_deref().unwrap_or(&a.name);
+                    full_key == arg_key || full_key.starts_with(&format!("{}.", arg_key))
+                });
+            if declared {
+                continue;
+            }
+
+            match value.as_table() {
+                Some(inner) => self.report_unknown_keys(inner, &full_key, f),
+                None => f(&full_key, value),
+            }
+        }
+    }
+
+    /// The first of the `config_search_paths` that exists
+    fn search_config(&self) -> Option<String> {
+        self.config_search_paths
+            .iter()
+            .map(|path| match (path.strip_prefix("~/"), env::var("HOME")) {
+                (Some(rest), Ok(home)) => Path::new(&home).join(rest).display().to_string(),
+                _ => path.clone(),
+            })
+            .find(|path| Path::new(path).is_file())
+    }
+
+    /// Parse a config file (resolving includes) and report it as loaded
+    fn load_config(&self, path: &str) -> Result<Table> {
+        let table = config::load_with_includes(Path::new(path), &mut cycle::CycleGuard::new())?;
+        self.emit(ParseEvent::ConfigLoaded {
+            path: path.to_string(),
+        });
+        Ok(table)
+    }
+
+    /// Report a resolution milestone to the observer, if any
+    fn emit(&self, event: ParseEvent) {
+        if let Some(Callback(f)) = &self.on_event {
+            f(event);
+        }
     }
 
     /// Load config file, creating it from template if needed
-    fn load_or_create_config(&self, path: Option<&str>) -> Result<Option<Table>> {
+    ///
+    /// Only the default config path is ever created; a path given with `-c`
+    /// is never written to.
+    fn load_or_create_config(&self, path: Option<&str>, is_default: bool) -> Result<Option<Table>> {
         match path {
             Some(p) => {
                 let path_exists = Path::new(p).exists();
 
                 // If file doesn't exist, try to create from template
                 if !path_exists {
-                    if let Some(template) = &self.config_template {
+                    if let Some(template) = &self.config_template
+                        && is_default
+                    {
                         // Write template to create the config file
                         std::fs::write(p, template)?;
+                        self.emit(ParseEvent::ConfigCreated {
+                            path: p.to_string(),
+                        });
                         // Now load it
-                        return Ok(Some(stoml::parse_file(p)?));
+                        return Ok(Some(self.load_config(p)?));
                     } else if self.config_required {
                         // No template and config is required - error
                         return Err(Error::MissingConfig {
@@ -478,7 +2333,7 @@ impl Args {
                 }
 
                 // File exists - load it
-                Ok(Some(stoml::parse_file(p)?))
+                Ok(Some(self.load_config(p)?))
             }
             None => {
                 // No path at all
@@ -491,36 +2346,125 @@ impl Args {
                 }
             }
         }
-    }
+    }
+
+    /// Describe a single argument in detail, or `None` if it isn't defined
+    ///
+    /// Covers its flags, type, default, possible values, config key, and help
+    /// text. Useful for a `myapp --explain port` style command.
+    pub fn explain_arg(&self, name: &str) -> Option<String> {
+        let arg = self.args.iter().find(|a| a.name == name)?;
+        let mut out = String::new();
+
+        // Flags line, e.g. "-p, --port <PORT>" or "<INPUT>"
+        let mut flags = Vec::new();
+        if let Some(c) = arg.short {
+            flags.push(format!("-{}", c));
+        }
+        if let Some(l) = &arg.long {
+            flags.push(format!("--{}", l));
+        }
+        let mut line = flags.join(", ");
+        if arg.positional {
+            let vname = arg.value_name.as_deref().unwrap_or(&arg.name);
+            line = format!("<{}>", vname.to_uppercase());
+        } else if let Some(placeholder) = arg.placeholder() {
+            line.push_str(&format!(" {}", placeholder));
+        }
+        out.push_str(line.trim_start());
+        out.push('\n');
 
-    /// Format help message
-    fn format_help(&self) -> String {
-        let mut help = String::new();
+        if let Some(h) = &arg.help {
+            out.push_str(&format!("    {}\n", h));
+        }
+        out.push('\n');
+
+        out.push_str(&format!("    Type: {}\n", arg.arg_type.type_name()));
+        out.push_str(&format!(
+            "    Required: {}\n",
+            if arg.required { "yes" } else { "no" }
+        ));
+        if let Some(hint) = &arg.default_value_hint {
+            out.push_str(&format!("    Default: {}\n", hint));
+        } else if let Some(d) = &arg.default {
+            out.push_str(&format!("    Default: {}\n", d));
+        }
+        if !arg.possible_values.is_empty() {
+            out.push_str(&format!(
+                "    Possible values: {}\n",
+                arg.possible_values.join(", ")
+            ));
+        }
+        if let Some(key) = &arg.toml_key {
+            out.push_str(&format!("    Config key: {}\n", key));
+        }
+        if let Some(var) = &arg.env {
+            out.push_str(&format!("    Env: {}\n", var));
+        }
+        if arg.experimental {
+            out.push_str("    Experimental: yes\n");
+        }
 
-        // Usage line
-        help.push_str(&format!("Usage: {}", self.name));
+        Some(out)
+    }
 
-        // Collect positionals
-        let positionals: Vec<_> = self.args.iter().filter(|a| a.positional).collect();
+    /// Format the arguments part of the usage line (e.g. " [OPTIONS] <FILE>")
+    fn usage_suffix(&self) -> String {
+        let mut usage = String::new();
 
         if self.args.iter().any(|a| !a.positional) {
-            help.push_str(" [OPTIONS]");
+            usage.push_str(" [OPTIONS]");
         }
 
-        for arg in &positionals {
+        for arg in self.args.iter().filter(|a| a.positional) {
+            if !arg.group.is_empty() {
+                let members: Vec<String> = arg
+                    .group
+                    .iter()
+                    .map(|m| format!("<{}>", m.name.to_uppercase()))
+                    .collect();
+                usage.push_str(&format!(" [{}]...", members.join(" ")));
+                continue;
+            }
             let name = arg.value_name.as_deref().unwrap_or(&arg.name);
             if arg.required {
-                help.push_str(&format!(" <{}>", name.to_uppercase()));
+                usage.push_str(&format!(" <{}>", name.to_uppercase()));
             } else {
-                help.push_str(&format!(" [{}]", name.to_uppercase()));
+                usage.push_str(&format!(" [{}]", name.to_uppercase()));
             }
             if arg.variadic {
-                help.push_str("...");
+                usage.push_str("...");
             }
         }
 
+        if !self.subcommands.is_empty() {
+            usage.push_str(" [COMMAND]");
+        }
+
+        usage
+    }
+
+    /// Options to list in help (`all` includes experimental options)
+    fn help_options(&self, all: bool) -> Vec<&Arg> {
+        self.args
+            .iter()
+            .chain(&self.disabled_experimental)
+            .filter(|a| !a.positional && (all || !a.experimental))
+            .collect()
+    }
+
+    /// Format help message (`all` includes experimental options)
+    fn format_help(&self, all: bool) -> String {
+        let mut help = String::new();
+
+        // Usage line
+        let name = self.usage_name.as_deref().unwrap_or(&self.name);
+        help.push_str(&format!("Usage: {}{}", name, self.usage_suffix()));
         help.push('\n');
 
+        // Collect positionals
+        let positionals: Vec<_> = self.args.iter().filter(|a| a.positional).collect();
+
         // Description
         if let Some(about) = &self.about {
             help.push('\n');
@@ -528,6 +2472,21 @@ impl Args {
             help.push('\n');
         }
 
+        // Subcommands
+        if !self.subcommands.is_empty() {
+            help.push_str("\nCommands:\n");
+            for sub in &self.subcommands {
+                let mut line = format!("  {}", sub.name);
+                if let Some(about) = &sub.about {
+                    let pad = 28usize.saturating_sub(line.len());
+                    line.push_str(&" ".repeat(pad));
+                    line.push_str(about);
+                }
+                help.push_str(&line);
+                help.push('\n');
+            }
+        }
+
         // Positional arguments
         if !positionals.is_empty() {
             help.push_str("\nArguments:\n");
@@ -542,7 +2501,7 @@ impl Args {
         }
 
         // Options
-        let options: Vec<_> = self.args.iter().filter(|a| !a.positional).collect();
+        let options = self.help_options(all);
         if !options.is_empty() {
             help.push_str("\nOptions:\n");
             for arg in &options {
@@ -564,13 +2523,8 @@ impl Args {
                 }
 
                 // Value placeholder
-                if arg.arg_type != ArgType::Bool && arg.arg_type != ArgType::Count {
-                    let vname = arg
-                        .value_name
-                        .as_deref()
-                        .unwrap_or(&arg.name)
-                        .to_uppercase();
-                    line.push_str(&format!(" <{}>", vname));
+                if let Some(placeholder) = arg.placeholder() {
+                    line.push_str(&format!(" {}", placeholder));
                 }
 
                 // Pad for alignment
@@ -581,13 +2535,38 @@ impl Args {
                 if let Some(h) = &arg.help {
                     line.push_str(h);
                 }
+                if arg.experimental {
+                    line.push_str(" (experimental)");
+                }
+
+                if let Some(var) = &arg.env {
+                    line.push_str(&format!(" [env: {}]", var));
+                }
+
+                if let Some((min, max)) = &arg.range {
+                    line.push_str(&format!(" [range: {}..={}]", min, max));
+                }
 
                 // Default value
-                if let Some(d) = &arg.default
+                if let Some(hint) = &arg.default_value_hint {
+                    line.push_str(&format!(" [default: {}]", hint));
+                } else if let Some(d) = &arg.default
                     && !matches!(d, Value::Boolean(false) | Value::Integer(0)) {
                         line.push_str(&format!(" [default: {}]", d));
                     }
 
+                // Possible values, wrapped onto their own line if too long
+                if self.show_possible_values && !arg.possible_values.is_empty() {
+                    let values = format!("[possible values: {}]", arg.possible_values.join(", "));
+                    if line.len() + values.len() + 1 > HELP_WIDTH {
+                        line.push('\n');
+                        line.push_str(&" ".repeat(28));
+                    } else {
+                        line.push(' ');
+                    }
+                    line.push_str(&values);
+                }
+
                 help.push_str(&line);
                 help.push('\n');
             }
@@ -596,13 +2575,18 @@ impl Args {
         help
     }
 
-    /// Format version message
-    fn format_version(&self) -> String {
-        format!(
+    /// Format version message (`detailed` appends the version details)
+    fn format_version(&self, detailed: bool) -> String {
+        let mut version = format!(
             "{} {}",
             self.name,
             self.version.as_deref().unwrap_or("unknown")
-        )
+        );
+        if detailed && let Some(Callback(details)) = &self.version_details {
+            version.push('\n');
+            version.push_str(details().trim_end());
+        }
+        version
     }
 }
 
@@ -615,6 +2599,32 @@ pub struct Matches {
     program_name: String,
     /// Raw remaining arguments
     remaining: Vec<String>,
+    /// External subcommand name and its arguments
+    external_subcommand: Option<(String, Vec<String>)>,
+    /// Chosen subcommand and its matches
+    subcommand: Option<(String, Box<Matches>)>,
+    /// Subcommand tokens waiting to be handed to the child parser
+    pending_subcommand: Option<(String, Vec<String>)>,
+    /// Where each value came from
+    sources: HashMap<String, ValueSource>,
+    /// Keys set in config or the environment but overridden by a CLI value
+    overridden: Vec<String>,
+    /// Non-fatal issues noticed while resolving values
+    warnings: Vec<Warning>,
+    /// Observer carried over from `Args::on_parse_event`
+    on_event: Option<Callback<EventFn>>,
+    /// Arg names whose canonical key is a different `toml_key`
+    toml_keys: HashMap<String, String>,
+    /// Arg name for each alias and `toml_key`, used by the getters
+    arg_names: HashMap<String, String>,
+    /// Number of positional values given on the command line
+    positional_count: usize,
+    /// Original bytes of values that weren't valid UTF-8
+    os_values: HashMap<String, OsString>,
+    /// Original bytes of array elements that weren't valid UTF-8, by index
+    os_elements: HashMap<String, HashMap<usize, OsString>>,
+    /// Path of the config file that was loaded
+    config_path: Option<String>,
 }
 
 impl Matches {
@@ -623,6 +2633,19 @@ impl Matches {
             values: HashMap::new(),
             program_name: String::new(),
             remaining: Vec::new(),
+            external_subcommand: None,
+            subcommand: None,
+            pending_subcommand: None,
+            sources: HashMap::new(),
+            overridden: Vec::new(),
+            warnings: Vec::new(),
+            on_event: None,
+            toml_keys: HashMap::new(),
+            arg_names: HashMap::new(),
+            positional_count: 0,
+            os_values: HashMap::new(),
+            os_elements: HashMap::new(),
+            config_path: None,
         }
     }
 
@@ -632,12 +2655,90 @@ impl Matches {
         self
     }
 
+    /// Merge TOML configuration, replacing values that are already set
+    ///
+    /// The opposite direction to `with_toml`: every key in `table` wins, so
+    /// defaults can be applied first and the config laid over them. Values
+    /// are replaced by key, so a value under an arg's name is left alone by
+    /// a config value under its `toml_key`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// # let arg_defs = vec![arg("port").long("port").default(80)];
+    /// # let parser = arg_defs.iter().cloned().fold(args("myapp"), Args::arg);
+    /// # let config = Table::new();
+    /// // The config beats the defaults, and command-line values too
+    /// let matches = parser
+    ///     .parse()?
+    ///     .with_defaults(&arg_defs)
+    ///     .with_toml_override(&config);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_toml_override(mut self, table: &Table) -> Self {
+        self.override_toml(table);
+        self
+    }
+
+    /// Replace values with those in `table` (see `with_toml_override`)
+    pub fn override_toml(&mut self, table: &Table) {
+        self.replace_toml(table, "");
+    }
+
+    /// Build a Matches from TOML text alone, without any CLI parsing
+    ///
+    /// Handy for unit-testing code that consumes a `Matches`, and for
+    /// config-only tools.
+    pub fn from_toml_str(s: &str, program_name: &str) -> Result<Self> {
+        let mut matches = Matches::new().with_toml_str(s)?;
+        matches.program_name = program_name.to_string();
+        Ok(matches)
+    }
+
+    /// Merge with TOML text (parses the string)
+    pub fn with_toml_str(self, s: &str) -> Result<Self> {
+        let table = stoml::parse(s)?;
+        Ok(self.with_toml(&table))
+    }
+
     /// Merge with TOML file (reads and parses the file)
+    ///
+    /// The file's `include` directives are resolved as for `Args::config_arg`.
     pub fn with_toml_file<P: AsRef<Path>>(self, path: P) -> Result<Self> {
-        let table = stoml::parse_file(path)?;
+        let table = config::load_with_includes(path.as_ref(), &mut cycle::CycleGuard::new())?;
         Ok(self.with_toml(&table))
     }
 
+    /// Merge several TOML files, later files taking precedence
+    ///
+    /// The files are deep-merged in the order given, so a later file
+    /// overrides single keys of a table without dropping the rest of it.
+    /// Values already present (e.g. from the command line) still win over
+    /// every file, so the precedence is CLI > last file > ... > first file.
+    /// Each file's `include` directives are resolved first, as for
+    /// `with_toml_file`. Handy for a base config plus an environment-specific
+    /// one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// # let parser = args("myapp");
+    /// let matches = parser.parse()?.with_toml_files(&["base.toml", "prod.toml"])?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_toml_files<P: AsRef<Path>>(self, paths: &[P]) -> Result<Self> {
+        let mut merged = Table::new();
+        for path in paths {
+            let table = config::load_with_includes(path.as_ref(), &mut cycle::CycleGuard::new())?;
+            config::merge_tables(&mut merged, table);
+        }
+        Ok(self.with_toml(&merged))
+    }
+
     /// Merge with TOML file if it exists (does not error if missing)
     pub fn with_toml_file_optional<P: AsRef<Path>>(self, path: P) -> Result<Self> {
         if path.as_ref().exists() {
@@ -647,17 +2748,144 @@ impl Matches {
         }
     }
 
+    /// Fill in everything the command line didn't set, in one call
+    ///
+    /// Reads each absent arg's environment variable (see `Arg::env`), then
+    /// applies `toml` (if given) and the args' defaults, so the precedence is
+    /// CLI > env > TOML > default. Env values that don't parse are skipped.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// # let arg_defs = vec![arg("port").long("port")];
+    /// # let parser = arg_defs.iter().cloned().fold(args("myapp"), Args::arg);
+    /// # let config = Table::new();
+    /// let matches = parser.parse()?.resolve(&arg_defs, Some(&config));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve(mut self, args: &[Arg], toml: Option<&Table>) -> Self {
+        let parser = ArgParser::new(args);
+        for (idx, arg) in args.iter().enumerate() {
+            let Some(var) = &arg.env else {
+                continue;
+            };
+            if self.values.contains_key(&arg.name) {
+                let env_set = env::var_os(var).is_some()
+                    || (arg.arg_type == ArgType::Array
+                        && !indexed_env_values(var, |v| env::var(v).ok()).is_empty());
+                if env_set {
+                    self.note_overridden(&arg.name);
+                }
+                continue;
+            }
+            if let Ok(value) = env::var(var) {
+                if parser.set_env_value(idx, &value, &mut self).is_ok() {
+                    self.emit(ParseEvent::EnvResolved { var: var.clone() });
+                }
+            } else if arg.arg_type == ArgType::Array {
+                let elements = indexed_env_values(var, |v| env::var(v).ok());
+                if !elements.is_empty()
+                    && parser.set_env_elements(idx, &elements, &mut self).is_ok()
+                {
+                    for i in 0..elements.len() {
+                        self.emit(ParseEvent::EnvResolved {
+                            var: format!("{}_{}", var, i),
+                        });
+                    }
+                }
+            }
+        }
+
+        let resolved = match toml {
+            Some(table) => {
+                self.merge_toml(table, "");
+                self.coerce_bools(args);
+                self
+            }
+            None => self,
+        };
+        resolved.with_defaults(args)
+    }
+
     /// Apply defaults from argument definitions
+    ///
+    /// Plain defaults are applied first, so conditional defaults (see
+    /// `Arg::default_if`) can refer to args that were filled in by a default.
     pub fn with_defaults(mut self, args: &[Arg]) -> Self {
-        for arg in args {
-            if !self.values.contains_key(&arg.name)
-                && let Some(default) = &arg.default {
-                    self.values.insert(arg.name.clone(), default.clone());
-                }
+        self.record_keys(args);
+        let (plain, conditional): (Vec<&Arg>, Vec<&Arg>) =
+            args.iter().partition(|a| a.default_ifs.is_empty());
+
+        for arg in plain.into_iter().chain(conditional) {
+            if self.values.contains_key(&arg.name) {
+                continue;
+            }
+            let default = arg
+                .default_ifs
+                .iter()
+                .find(|(other, eq, _)| self.get(other) == Some(eq))
+                .map(|(_, _, then)| then)
+                .or(arg.default.as_ref());
+            if let Some(default) = default {
+                self.values.insert(arg.name.clone(), default.clone());
+                self.sources.insert(arg.name.clone(), ValueSource::Default);
+                self.emit(ParseEvent::DefaultApplied {
+                    name: arg.name.clone(),
+                });
+            }
         }
         self
     }
 
+    /// Report a resolution milestone to the parser's observer, if any
+    fn emit(&self, event: ParseEvent) {
+        if let Some(Callback(f)) = &self.on_event {
+            f(event);
+        }
+    }
+
+    /// Remember that a CLI value beat a config or environment value for `key`
+    fn note_overridden(&mut self, key: &str) {
+        if self.sources.get(key) == Some(&ValueSource::Cli)
+            && !self.overridden.iter().any(|k| k == key)
+        {
+            self.overridden.push(key.to_string());
+        }
+    }
+
+    /// Turn config strings like "yes" or "off" into booleans for Bool args
+    fn coerce_bools(&mut self, args: &[Arg]) {
+        for arg in args.iter().filter(|a| a.arg_type == ArgType::Bool) {
+            let key = arg.toml_key.as_deref().unwrap_or(&arg.name);
+            if let Some(b) = self
+                .values
+                .get(key)
+                .and_then(|v| v.as_str())
+                .and_then(parse_bool)
+            {
+                self.values.insert(key.to_string(), Value::Boolean(b));
+            }
+        }
+    }
+
+    /// Remember each arg's `toml_key` and aliases, so values can be found
+    /// under any of them
+    fn record_keys(&mut self, args: &[Arg]) {
+        for arg in args {
+            if let Some(key) = &arg.toml_key
+                && *key != arg.name
+            {
+                self.toml_keys.insert(arg.name.clone(), key.clone());
+                self.arg_names.insert(key.clone(), arg.name.clone());
+            }
+            for alias in &arg.aliases {
+                self.arg_names.insert(alias.clone(), arg.name.clone());
+            }
+        }
+    }
+
     fn merge_toml(&mut self, table: &Table, prefix: &str) {
         for (key, value) in table.iter() {
             let full_key = if prefix.is_empty() {
@@ -672,23 +2900,129 @@ impl Matches {
             }
 
             // Only insert if not already set (CLI takes precedence)
-            self.values.entry(full_key).or_insert_with(|| value.clone());
+            if self.values.contains_key(&full_key) {
+                self.note_overridden(&full_key);
+            } else {
+                self.values.insert(full_key.clone(), value.clone());
+                self.sources.insert(full_key, ValueSource::Toml);
+            }
+        }
+    }
+
+    /// Insert every key of a TOML table, replacing existing values
+    fn replace_toml(&mut self, table: &Table, prefix: &str) {
+        for (key, value) in table.iter() {
+            let full_key = if prefix.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+
+            if let Some(inner) = value.as_table() {
+                self.replace_toml(inner, &full_key);
+            }
+
+            self.values.insert(full_key.clone(), value.clone());
+            self.sources.insert(full_key, ValueSource::Toml);
+        }
+    }
+
+    /// Whether the user supplied a value for `arg`, either under its name or
+    /// (from config) under its TOML key; defaults don't count
+    fn is_supplied(&self, arg: &Arg) -> bool {
+        let key = arg.toml_key.as_deref().unwrap_or(&arg.name);
+        [arg.name.as_str(), key].iter().any(|k| {
+            self.values.contains_key(*k) && self.sources.get(*k) != Some(&ValueSource::Default)
+        })
+    }
+
+    /// Get the one argument of `names` that was given
+    ///
+    /// Only values from the command line, a prompt, the environment or the
+    /// config count; defaults, implied values and explicit `false` negations
+    /// (`--no-json`) don't. Names may be aliases or `toml_key`s, and the
+    /// matching entry of `names` is returned.
+    ///
+    /// Fails with `Error::MissingRequiredGroup` if none was given, or
+    /// `Error::ConflictingArgs` if more than one was. Handy for "exactly one
+    /// of" rules decided at the call site.
+    pub fn require_one_of<'a>(&self, names: &[&'a str]) -> Result<&'a str> {
+        let present: Vec<&str> = names.iter().copied().filter(|n| self.is_given(n)).collect();
+        match present.as_slice() {
+            [one] => Ok(one),
+            [] => Err(Error::MissingRequiredGroup {
+                names: names.iter().map(|n| n.to_string()).collect(),
+            }),
+            _ => Err(Error::ConflictingArgs {
+                names: present.iter().map(|n| n.to_string()).collect(),
+            }),
         }
     }
 
+    /// Whether the user gave `name` (CLI, prompt, env or config, not `false`)
+    fn is_given(&self, name: &str) -> bool {
+        let name = self.arg_name(name);
+        let key = self.toml_keys.get(name).map_or(name, String::as_str);
+        [name, key].iter().any(|k| {
+            matches!(
+                self.sources.get(*k),
+                Some(ValueSource::Cli | ValueSource::Prompt | ValueSource::Env | ValueSource::Toml)
+            ) && self.values.get(*k) != Some(&Value::Boolean(false))
+        })
+    }
+
     /// Check if an argument was provided
     pub fn contains(&self, name: &str) -> bool {
-        self.values.contains_key(name)
+        self.get(name).is_some()
     }
 
     /// Get a value by name
+    ///
+    /// `name` may also be one of the arg's aliases or its `toml_key`. A value
+    /// under the arg's name wins, unless it is only a default and the config
+    /// set the `toml_key`. All typed getters resolve names the same way.
     pub fn get(&self, name: &str) -> Option<&Value> {
-        self.values.get(name)
+        let name = self.arg_name(name);
+        let direct = self.values.get(name);
+        let by_key = self.toml_keys.get(name).and_then(|k| self.values.get(k));
+        match (direct, by_key) {
+            (Some(_), Some(v)) if self.sources.get(name) == Some(&ValueSource::Default) => Some(v),
+            (direct, by_key) => direct.or(by_key),
+        }
+    }
+
+    /// The arg name behind an alias or `toml_key`, or `name` itself
+    fn arg_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.arg_names.get(name).map_or(name, |n| n.as_str())
     }
 
     /// Get a string value
     pub fn get_string(&self, name: &str) -> Option<&str> {
-        self.values.get(name).and_then(|v| v.as_str())
+        self.get(name).and_then(|v| v.as_str())
+    }
+
+    /// Get a string value as a path
+    pub fn get_path(&self, name: &str) -> Option<PathBuf> {
+        self.get_os(name).map(PathBuf::from)
+    }
+
+    /// Get a string value as an `OsStr`
+    ///
+    /// For a value given to `Args::parse_from_os` that wasn't valid UTF-8,
+    /// this is the original, unlike the lossy `get_string`.
+    pub fn get_os(&self, name: &str) -> Option<&OsStr> {
+        match self.os_values.get(self.arg_name(name)) {
+            Some(os) => Some(os),
+            None => self.get_string(name).map(OsStr::new),
+        }
+    }
+
+    /// Get any value rendered as plain text, regardless of its type
+    ///
+    /// Strings are returned unquoted and arrays are comma-joined. Useful for
+    /// logging and templating; prefer the typed getters otherwise.
+    pub fn get_raw(&self, name: &str) -> Option<String> {
+        self.get(name).map(raw_string)
     }
 
     /// Get a string value or default
@@ -700,7 +3034,7 @@ impl Matches {
 
     /// Get an integer value
     pub fn get_integer(&self, name: &str) -> Option<i64> {
-        self.values.get(name).and_then(|v| v.as_integer())
+        self.get(name).and_then(|v| v.as_integer())
     }
 
     /// Get an integer value or default
@@ -708,9 +3042,14 @@ impl Matches {
         self.get_integer(name).unwrap_or(default)
     }
 
+    /// Get an integer value as `u64`, or None if missing or negative
+    pub fn get_u64(&self, name: &str) -> Option<u64> {
+        self.get_integer(name).and_then(|i| u64::try_from(i).ok())
+    }
+
     /// Get a float value
     pub fn get_float(&self, name: &str) -> Option<f64> {
-        self.values.get(name).and_then(|v| v.as_float())
+        self.get(name).and_then(|v| v.as_float())
     }
 
     /// Get a float value or default
@@ -718,35 +3057,138 @@ impl Matches {
         self.get_float(name).unwrap_or(default)
     }
 
+    /// Get a duration value
+    ///
+    /// Reads the milliseconds stored by an `ArgType::Duration` arg, or parses
+    /// a string such as "1h30m" (e.g. from a config file). None if missing,
+    /// negative or unparseable.
+    pub fn get_duration(&self, name: &str) -> Option<Duration> {
+        let millis = match self.get(name)? {
+            Value::Integer(ms) => *ms,
+            Value::String(s) => parser::parse_duration(s)?,
+            _ => return None,
+        };
+        u64::try_from(millis).ok().map(Duration::from_millis)
+    }
+
+    /// Get a socket address value
+    pub fn get_socket_addr(&self, name: &str) -> Option<SocketAddr> {
+        self.get_string(name).and_then(|s| s.parse().ok())
+    }
+
+    /// Get an IP address value (returns None for hostnames)
+    pub fn get_ip(&self, name: &str) -> Option<IpAddr> {
+        self.get_string(name).and_then(|s| s.parse().ok())
+    }
+
     /// Get a boolean value (returns false if not present)
     pub fn get_bool(&self, name: &str) -> bool {
-        self.values
-            .get(name)
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false)
+        self.get(name).and_then(|v| v.as_bool()).unwrap_or(false)
     }
 
     /// Get an optional boolean value (returns None if not present)
     pub fn get_bool_opt(&self, name: &str) -> Option<bool> {
-        self.values.get(name).and_then(|v| v.as_bool())
+        self.get(name).and_then(|v| v.as_bool())
     }
 
     /// Get an array value
     pub fn get_array(&self, name: &str) -> Option<&Array> {
-        self.values.get(name).and_then(|v| v.as_array())
+        self.get(name).and_then(|v| v.as_array())
+    }
+
+    /// Get one element of an array value, or None if absent or out of bounds
+    pub fn get_array_element(&self, name: &str, index: usize) -> Option<&Value> {
+        self.get_array(name).and_then(|a| a.get(index))
+    }
+
+    /// Get the length of an array value (0 if absent or not an array)
+    pub fn array_len(&self, name: &str) -> usize {
+        self.get_array(name).map_or(0, |a| a.len())
+    }
+
+    /// Get an array of strings, or the given default list if absent
+    pub fn get_string_array_or(&self, name: &str, default: &[&str]) -> Vec<String> {
+        match self.get_array(name) {
+            Some(a) => a
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect(),
+            None => default.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// Get the string elements of an array (empty if absent)
+    ///
+    /// Elements of other types are skipped.
+    pub fn get_string_vec(&self, name: &str) -> Vec<String> {
+        self.get_array(name)
+            .map(|a| {
+                a.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the string elements of an array as `OsString`s (empty if absent)
+    ///
+    /// Elements given to `Args::parse_from_os` that weren't valid UTF-8 are
+    /// the originals, unlike the lossy `get_string_vec`.
+    pub fn get_os_vec(&self, name: &str) -> Vec<OsString> {
+        let originals = self.os_elements.get(self.arg_name(name));
+        self.get_array(name)
+            .map(|a| {
+                a.iter()
+                    .enumerate()
+                    .filter_map(|(i, v)| match originals.and_then(|o| o.get(&i)) {
+                        Some(os) => Some(os.clone()),
+                        None => v.as_str().map(OsString::from),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get the integer elements of an array (empty if absent)
+    ///
+    /// Elements of other types are skipped.
+    pub fn get_integer_vec(&self, name: &str) -> Vec<i64> {
+        self.get_array(name)
+            .map(|a| a.iter().filter_map(|v| v.as_integer()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the float elements of an array (empty if absent)
+    ///
+    /// Elements of other types are skipped.
+    pub fn get_float_vec(&self, name: &str) -> Vec<f64> {
+        self.get_array(name)
+            .map(|a| a.iter().filter_map(|v| v.as_float()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get a map value (e.g. flags collected by `Args::capture_unknown_into`)
+    pub fn get_map(&self, name: &str) -> Option<&Table> {
+        self.get(name).and_then(|v| v.as_table())
+    }
+
+    /// Get the records collected by a positional group
+    pub fn get_records(&self, name: &str) -> Vec<&Table> {
+        self.get_array(name)
+            .map(|a| a.iter().filter_map(|v| v.as_table()).collect())
+            .unwrap_or_default()
     }
 
     /// Get a count value (returns 0 if not present)
     pub fn get_count(&self, name: &str) -> i64 {
-        self.values
-            .get(name)
-            .and_then(|v| v.as_integer())
-            .unwrap_or(0)
+        self.get(name).and_then(|v| v.as_integer()).unwrap_or(0)
     }
 
     /// Get an optional count value (returns None if not present)
     pub fn get_count_opt(&self, name: &str) -> Option<i64> {
-        self.values.get(name).and_then(|v| v.as_integer())
+        self.get(name).and_then(|v| v.as_integer())
     }
 
     /// Get remaining unparsed arguments
@@ -754,15 +3196,214 @@ impl Matches {
         &self.remaining
     }
 
+    /// Path of the config file that was loaded, if any
+    ///
+    /// Whether it came from `-c`, `config_env`, `config_search_paths` or the
+    /// default path.
+    pub fn config_path(&self) -> Option<&str> {
+        self.config_path.as_deref()
+    }
+
+    /// Get where a value came from (CLI, TOML, implied, or default)
+    pub fn source(&self, name: &str) -> Option<ValueSource> {
+        self.sources.get(name).copied()
+    }
+
+    /// Get the keys that were set in config or the environment but overridden
+    /// on the command line
+    ///
+    /// Keys are sorted.
+    pub fn overridden(&self) -> Vec<String> {
+        let mut keys = self.overridden.clone();
+        keys.sort();
+        keys
+    }
+
+    /// Split the arguments after `--` into flag/value pairs
+    ///
+    /// This is a best-effort tokenizer for forwarding to another tool, not a
+    /// full parse: `--key=value` and `--key value` yield `("key", Some(value))`,
+    /// and a flag followed by another flag (or nothing) yields `("key", None)`.
+    /// Short flags are handled the same way. A value is only taken from the
+    /// next token if it doesn't start with `-` or is a negative number
+    /// (`-5`, `-0.5`). Tokens that are not flags or values are skipped.
+    pub fn remaining_as_pairs(&self) -> Vec<(String, Option<String>)> {
+        let mut pairs = Vec::new();
+        let mut iter = self.remaining.iter().peekable();
+        while let Some(token) = iter.next() {
+            let key = match token.strip_prefix("--").or_else(|| token.strip_prefix('-')) {
+                Some(k) if !k.is_empty() => k,
+                _ => continue,
+            };
+            if let Some((k, v)) = key.split_once('=') {
+                pairs.push((k.to_string(), Some(v.to_string())));
+            } else {
+                let value = iter
+                    .next_if(|next| !next.starts_with('-') || is_negative_number(next))
+                    .cloned();
+                pairs.push((key.to_string(), value));
+            }
+        }
+        pairs
+    }
+
+    /// Get warnings recorded during parsing (e.g. config values of the wrong type)
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Get warnings rendered as human-readable strings
+    pub fn warnings_display(&self) -> Vec<String> {
+        self.warnings.iter().map(|w| w.to_string()).collect()
+    }
+
+    /// Get the external subcommand and its arguments, if one was given
+    pub fn external_subcommand(&self) -> Option<(String, Vec<String>)> {
+        self.external_subcommand.clone()
+    }
+
+    /// Get the chosen subcommand's name and matches, if one was given
+    pub fn subcommand(&self) -> Option<(&str, &Matches)> {
+        self.subcommand
+            .as_ref()
+            .map(|(name, matches)| (name.as_str(), matches.as_ref()))
+    }
+
+    /// Get the names of the chosen subcommands from outermost to innermost
+    ///
+    /// For `git remote add` this is `["remote", "add"]`; empty if no
+    /// subcommand was given.
+    pub fn subcommand_path(&self) -> Vec<&str> {
+        let mut path = Vec::new();
+        let mut current = self;
+        while let Some((name, sub)) = current.subcommand() {
+            path.push(name);
+            current = sub;
+        }
+        path
+    }
+
+    /// Get all values under a dotted key prefix, with the prefix stripped
+    ///
+    /// For example, `filter_prefix("tls")` turns `tls.cert` and `tls.key`
+    /// into `cert` and `key`. Keys are the canonical ones of `flat_map()`, so
+    /// CLI values for args with a dotted `toml_key` are included.
+    pub fn filter_prefix(&self, prefix: &str) -> HashMap<String, Value> {
+        let prefix = format!("{}.", prefix);
+        self.flat_map()
+            .into_iter()
+            .filter_map(|(k, v)| k.strip_prefix(&prefix).map(|rest| (rest.to_string(), v)))
+            .collect()
+    }
+
     /// Get all values as a reference to the internal map
     pub fn values(&self) -> &HashMap<String, Value> {
         &self.values
     }
 
+    /// Get a copy containing only values given on the command line
+    ///
+    /// TOML, implied, and default values are dropped; the program name,
+    /// remaining arguments, and any subcommand are kept. Useful for
+    /// re-layering from scratch or persisting only explicit choices.
+    pub fn cli_only(&self) -> Matches {
+        let mut matches = Matches::new();
+        for (name, source) in &self.sources {
+            if *source == ValueSource::Cli
+                && let Some(value) = self.values.get(name)
+            {
+                matches.values.insert(name.clone(), value.clone());
+                matches.sources.insert(name.clone(), ValueSource::Cli);
+            }
+        }
+        matches.program_name = self.program_name.clone();
+        matches.remaining = self.remaining.clone();
+        matches.external_subcommand = self.external_subcommand.clone();
+        matches.subcommand = self.subcommand.clone();
+        matches.toml_keys = self.toml_keys.clone();
+        matches.arg_names = self.arg_names.clone();
+        matches.os_values = self.os_values.clone();
+        matches.os_elements = self.os_elements.clone();
+        matches.on_event = self.on_event.clone();
+        matches
+    }
+
+    /// Get all values keyed by their canonical dotted key, sorted by key
+    ///
+    /// An arg's canonical key is its `toml_key` if it has one, otherwise its
+    /// name, so a CLI value for `port` and a config value for `server.port`
+    /// land on the same key. When both exist, the value under the arg's name
+    /// wins unless it is only a default. Config tables appear as their dotted
+    /// leaf keys.
+    pub fn flat_map(&self) -> BTreeMap<String, Value> {
+        let rank = |key: &String| match (self.toml_keys.contains_key(key), self.sources.get(key)) {
+            (true, Some(ValueSource::Default)) => 0,
+            (false, _) => 1,
+            (true, _) => 2,
+        };
+        let mut entries: Vec<(&String, &Value)> = self
+            .values
+            .iter()
+            .filter(|(k, v)| !(v.is_table() && self.sources.get(*k) == Some(&ValueSource::Toml)))
+            .collect();
+        // Higher-ranked entries come later and overwrite on collect
+        entries.sort_by_key(|(k, _)| rank(k));
+        entries
+            .into_iter()
+            .map(|(k, v)| (self.toml_keys.get(k).unwrap_or(k).clone(), v.clone()))
+            .collect()
+    }
+
+    /// Render all values as an indented tree grouped by dotted key, with each
+    /// leaf annotated by where its value came from
+    ///
+    /// Keys are sorted, so the output is stable between runs.
+    pub fn debug_tree(&self) -> String {
+        // Nested tables are represented by their dotted leaf keys
+        let mut keys: Vec<&String> = self
+            .values
+            .iter()
+            .filter(|(_, v)| !v.is_table())
+            .map(|(k, _)| k)
+            .collect();
+        keys.sort_by(|a, b| a.split('.').cmp(b.split('.')));
+
+        let mut tree = String::new();
+        let mut open: Vec<&str> = Vec::new();
+        for key in keys {
+            let parts: Vec<&str> = key.split('.').collect();
+            let (leaf, path) = parts.split_last().unwrap_or((&"", &[]));
+
+            // Close sections that this key is not under, then open new ones
+            let common = open.iter().zip(path).take_while(|(a, b)| a == b).count();
+            open.truncate(common);
+            for part in &path[common..] {
+                tree.push_str(&format!("{}{}\n", "  ".repeat(open.len()), part));
+                open.push(part);
+            }
+
+            let source = self
+                .sources
+                .get(key)
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            tree.push_str(&format!(
+                "{}{} = {} ({})\n",
+                "  ".repeat(open.len()),
+                leaf,
+                self.values[key],
+                source
+            ));
+        }
+        tree
+    }
+
     /// Convert to a Table (useful for serialization or further processing)
+    ///
+    /// Built from `flat_map`, so dotted keys become nested tables.
     pub fn to_table(&self) -> Table {
         let mut table = Table::new();
-        for (key, value) in &self.values {
+        for (key, value) in &self.flat_map() {
             // Handle dotted keys by creating nested tables
             let parts: Vec<&str> = key.split('.').collect();
             if parts.len() == 1 {
@@ -788,6 +3429,117 @@ impl Matches {
         }
         table
     }
+
+    /// Render all values as a TOML document, e.g. to save the effective config
+    ///
+    /// Built from `to_table`, so dotted keys become `[section]` tables. The
+    /// output is parsed back as a check, failing with `Error::Toml` if it
+    /// isn't valid TOML.
+    pub fn to_toml_string(&self) -> Result<String> {
+        let toml = config::to_toml_string(&self.to_table());
+        stoml::parse(&toml)?;
+        Ok(toml)
+    }
+}
+
+/// Env var that makes `parse_from` print the resolved config to stderr
+const DEBUG_CONFIG_ENV: &str = "STOML_ARGS_DEBUG";
+
+/// Compare dotted version strings numerically ("1.10" >= "1.9")
+fn version_at_least(version: &str, target: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split('.')
+            .map(|p| {
+                let digits: String = p.chars().take_while(|c| c.is_ascii_digit()).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect()
+    };
+    let (mut a, mut b) = (parts(version), parts(target));
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    a >= b
+}
+
+/// The status to exit with for `error`, given an `Args::error_exit_code`
+fn exit_status(error: &Error, error_exit_code: Option<i32>) -> i32 {
+    match error_exit_code {
+        Some(code) if !error.is_info_request() => code,
+        _ => error.exit_code(),
+    }
+}
+
+/// Collect `VAR_0`, `VAR_1`, ... until the first missing index
+fn indexed_env_values(var: &str, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    (0..)
+        .map_while(|i| lookup(&format!("{}_{}", var, i)))
+        .collect()
+}
+
+/// Parse a boolean word: true/false, yes/no, on/off or 1/0, in any case
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether a token is a negative number (`-5`, `-0.5`, `-.5`) rather than a flag
+fn is_negative_number(token: &str) -> bool {
+    token.strip_prefix('-').is_some_and(|rest| {
+        rest.starts_with(|c: char| c.is_ascii_digit() || c == '.') && rest.parse::<f64>().is_ok()
+    })
+}
+
+/// Get the value part of a non-UTF-8 `--flag=value` token
+fn attached_os_value(token: &OsStr) -> Option<OsString> {
+    let bytes = token.as_encoded_bytes();
+    if !bytes.starts_with(b"-") {
+        return None;
+    }
+    let eq = bytes.iter().position(|&b| b == b'=')?;
+    // SAFETY: the split is right after an ASCII `=`, which keeps both halves
+    // valid encoded strings
+    let value = unsafe { OsStr::from_encoded_bytes_unchecked(&bytes[eq + 1..]) };
+    value.to_str().is_none().then(|| value.to_os_string())
+}
+
+/// Whether a numeric value lies in `min..=max`; non-numeric values always do
+fn in_range(value: &Value, min: &Value, max: &Value) -> bool {
+    if let (Value::Integer(v), Value::Integer(lo), Value::Integer(hi)) = (value, min, max) {
+        return (lo..=hi).contains(&v);
+    }
+    let as_float = |v: &Value| match v {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    };
+    match (as_float(value), as_float(min), as_float(max)) {
+        (Some(v), Some(lo), Some(hi)) => (lo..=hi).contains(&v),
+        _ => true,
+    }
+}
+
+/// Render a value as plain text (unquoted strings, comma-joined arrays)
+fn raw_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(a) => a.iter().map(raw_string).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+/// Look up a dotted key path (e.g. "server.port") in a TOML table
+fn lookup_toml<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut current = table.get(parts.next()?)?;
+    for part in parts {
+        current = current.as_table()?.get(part)?;
+    }
+    Some(current)
 }
 
 /// Convenience function to create a new Args builder
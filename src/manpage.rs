@@ -0,0 +1,119 @@
+use crate::Args;
+
+impl Args {
+    /// Generate a man page (section 1) in roff format
+    ///
+    /// The page has NAME, SYNOPSIS, DESCRIPTION (from `about`), ARGUMENTS and
+    /// OPTIONS sections, built from the same definitions as `--help`. Auto
+    /// flags (`--help`, `--config`, ...) are added at parse time and are not
+    /// included.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let parser = stoml_args::args("myapp");
+    /// std::fs::write("man/myapp.1", parser.generate_manpage())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn generate_manpage(&self) -> String {
+        let mut page = String::new();
+
+        let version = self
+            .version
+            .as_deref()
+            .map(|v| format!("{} {}", self.name, v))
+            .unwrap_or_else(|| self.name.clone());
+        page.push_str(&format!(
+            ".TH {} 1 \"\" \"{}\"\n",
+            escape(&self.name.to_uppercase()),
+            escape(&version)
+        ));
+
+        // NAME
+        page.push_str(".SH NAME\n");
+        match &self.about {
+            Some(about) => page.push_str(&format!(
+                "{} \\- {}\n",
+                escape(&self.name),
+                escape(about.lines().next().unwrap_or(""))
+            )),
+            None => page.push_str(&format!("{}\n", escape(&self.name))),
+        }
+
+        // SYNOPSIS
+        page.push_str(".SH SYNOPSIS\n");
+        page.push_str(&format!(".B {}\n", escape(&self.name)));
+        let usage = self.usage_suffix();
+        if !usage.is_empty() {
+            page.push_str(&format!("{}\n", escape(usage.trim_start())));
+        }
+
+        // DESCRIPTION
+        if let Some(about) = &self.about {
+            page.push_str(".SH DESCRIPTION\n");
+            for line in about.lines() {
+                if line.trim().is_empty() {
+                    page.push_str(".PP\n");
+                } else {
+                    page.push_str(&format!("{}\n", escape(line)));
+                }
+            }
+        }
+
+        // ARGUMENTS
+        let positionals: Vec<_> = self.args.iter().filter(|a| a.positional).collect();
+        if !positionals.is_empty() {
+            page.push_str(".SH ARGUMENTS\n");
+            for arg in positionals {
+                let name = arg.value_name.as_deref().unwrap_or(&arg.name);
+                page.push_str(&format!(".TP\n.I {}\n", escape(&name.to_uppercase())));
+                if let Some(h) = &arg.help {
+                    page.push_str(&format!("{}\n", escape(h)));
+                }
+            }
+        }
+
+        // OPTIONS
+        let options = self.help_options(false);
+        if !options.is_empty() {
+            page.push_str(".SH OPTIONS\n");
+            for arg in options {
+                let mut flags = Vec::new();
+                if let Some(c) = arg.short {
+                    flags.push(format!("\\fB\\-{}\\fR", escape(&c.to_string())));
+                }
+                if let Some(l) = &arg.long {
+                    flags.push(format!("\\fB\\-\\-{}\\fR", escape(l)));
+                }
+                let mut line = flags.join(", ");
+                if let Some(placeholder) = arg.placeholder() {
+                    line.push_str(&format!(" \\fI{}\\fR", escape(&placeholder)));
+                }
+                page.push_str(&format!(".TP\n{}\n", line));
+
+                if let Some(h) = &arg.help {
+                    page.push_str(&format!("{}\n", escape(h)));
+                }
+                if !arg.possible_values.is_empty() {
+                    page.push_str(&format!(
+                        "Possible values: {}\n",
+                        escape(&arg.possible_values.join(", "))
+                    ));
+                }
+            }
+        }
+
+        page
+    }
+}
+
+/// Escape text for roff: backslashes, hyphens, and leading control characters
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
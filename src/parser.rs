@@ -1,9 +1,15 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
+use std::net::{IpAddr, SocketAddr};
+use std::num::IntErrorKind;
 
-use stoml::{Array, Value};
+use stoml::{Array, Table, Value};
 
 use crate::error::{Error, Result};
-use crate::{Arg, ArgType, Matches};
+use crate::{Arg, ArgType, Matches, ValueSource, Warning, attached_os_value};
+
+/// Largest edit distance at which an unknown long flag gets a suggestion
+const MAX_SUGGEST_DISTANCE: usize = 2;
 
 /// Internal argument parser
 pub struct ArgParser<'a> {
@@ -11,10 +17,43 @@ pub struct ArgParser<'a> {
     short_map: HashMap<char, usize>,
     /// Map from long flag to arg index
     long_map: HashMap<String, usize>,
+    /// Map from lowercased long flag to arg index, used when ignoring case
+    folded_long_map: HashMap<String, usize>,
     /// Positional arguments in order
     positionals: Vec<usize>,
     /// Reference to argument definitions
     args: &'a [Arg],
+    /// Whether unique prefixes of long flags are accepted
+    infer_long: bool,
+    /// Whether `--flag=value` is split into flag and value
+    equals_syntax: bool,
+    /// Names of args that only take an attached value
+    require_equals: Vec<String>,
+    /// Name to collect unknown long flags under, instead of erroring
+    capture_unknown: Option<String>,
+    /// Whether the first positional starts an external subcommand
+    external_subcommands: bool,
+    /// Whether unknown flags are passed through to `remaining`
+    ignore_unknown: bool,
+    /// Names of defined subcommands, each with its aliases
+    subcommands: Vec<(String, Vec<String>)>,
+    /// Whether invalid values fall back to the arg's default
+    lenient: bool,
+    /// Whether `+x` turns off Bool short flag `x`
+    plus_toggles: bool,
+    /// Whether long flags and possible values are matched regardless of case
+    ignore_case: bool,
+    /// Original `OsString` of each argv token that wasn't valid UTF-8
+    os_args: HashMap<usize, OsString>,
+}
+
+/// Where in argv a value was taken from, to find its original `OsString`
+#[derive(Clone, Copy)]
+enum Origin {
+    /// The whole token at this index
+    Token(usize),
+    /// The part after `=` of the token at this index
+    Attached(usize),
 }
 
 impl<'a> ArgParser<'a> {
@@ -30,7 +69,7 @@ impl<'a> ArgParser<'a> {
                 if let Some(c) = arg.short {
                     short_map.insert(c, i);
                 }
-                if let Some(ref l) = arg.long {
+                for l in arg.long.iter().chain(&arg.aliases) {
                     long_map.insert(l.clone(), i);
                 }
             }
@@ -42,16 +81,251 @@ impl<'a> ArgParser<'a> {
         ArgParser {
             short_map,
             long_map,
+            folded_long_map: HashMap::new(),
             positionals,
             args,
+            infer_long: false,
+            equals_syntax: true,
+            require_equals: Vec::new(),
+            capture_unknown: None,
+            external_subcommands: false,
+            ignore_unknown: false,
+            subcommands: Vec::new(),
+            lenient: false,
+            plus_toggles: false,
+            ignore_case: false,
+            os_args: HashMap::new(),
         }
     }
 
+    /// Accept unique prefixes of long flags (e.g. `--verb` for `--verbose`)
+    pub fn infer_long_args(mut self, enabled: bool) -> Self {
+        self.infer_long = enabled;
+        self
+    }
+
+    /// Accept or reject the `--flag=value` form
+    pub fn equals_syntax(mut self, enabled: bool) -> Self {
+        self.equals_syntax = enabled;
+        self
+    }
+
+    /// Only accept attached values (`--flag=value`) for the named args
+    pub fn require_equals(mut self, names: Vec<String>) -> Self {
+        self.require_equals = names;
+        self
+    }
+
+    /// Collect unknown long flags into a table under `name`
+    pub fn capture_unknown_into(mut self, name: Option<String>) -> Self {
+        self.capture_unknown = name;
+        self
+    }
+
+    /// Capture the first positional and everything after it as an external
+    /// subcommand
+    pub fn external_subcommands(mut self, enabled: bool) -> Self {
+        self.external_subcommands = enabled;
+        self
+    }
+
+    /// Pass unknown flags through to `remaining` instead of erroring
+    pub fn ignore_unknown_flags(mut self, enabled: bool) -> Self {
+        self.ignore_unknown = enabled;
+        self
+    }
+
+    /// Replace invalid values with the arg's default instead of erroring
+    pub fn lenient(mut self, enabled: bool) -> Self {
+        self.lenient = enabled;
+        self
+    }
+
+    /// Accept `+x` to turn off Bool short flags
+    pub fn plus_minus_toggles(mut self, enabled: bool) -> Self {
+        self.plus_toggles = enabled;
+        self
+    }
+
+    /// Match long flags and possible values regardless of case
+    pub fn ignore_case(mut self, enabled: bool) -> Self {
+        self.ignore_case = enabled;
+        self.folded_long_map = if enabled {
+            self.long_map
+                .iter()
+                .map(|(long, &idx)| (long.to_lowercase(), idx))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        self
+    }
+
+    /// Keep the originals of non-UTF-8 tokens, keyed by their argv index
+    pub fn os_args(mut self, originals: HashMap<usize, OsString>) -> Self {
+        self.os_args = originals;
+        self
+    }
+
+    /// Hand off to a subcommand when its name or an alias is the first
+    /// positional
+    pub fn subcommands(mut self, names: Vec<(String, Vec<String>)>) -> Self {
+        self.subcommands = names;
+        self
+    }
+
+    /// The canonical name of the subcommand called `token`, if any
+    fn find_subcommand(&self, token: &str) -> Option<&str> {
+        self.subcommands
+            .iter()
+            .find(|(name, aliases)| name == token || aliases.iter().any(|a| a == token))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The subcommand name or alias closest to a mistyped one, if any is
+    /// close enough
+    fn suggest_subcommand(&self, token: &str) -> Option<&str> {
+        self.subcommands
+            .iter()
+            .flat_map(|(name, aliases)| std::iter::once(name).chain(aliases))
+            .map(|name| (edit_distance(token, name), name.as_str()))
+            .filter(|&(distance, _)| distance <= MAX_SUGGEST_DISTANCE)
+            .min()
+            .map(|(_, name)| name)
+    }
+
+    /// The known long flag closest to a mistyped one, if any is close enough
+    fn suggest_long(&self, name: &str) -> Option<&str> {
+        let name = self.fold(name);
+        self.long_map
+            .keys()
+            .map(|long| (edit_distance(&name, &self.fold(long)), long.as_str()))
+            .filter(|&(distance, _)| distance <= MAX_SUGGEST_DISTANCE)
+            .min()
+            .map(|(_, long)| long)
+    }
+
+    /// Resolve a long flag name to an arg index
+    ///
+    /// Exact matches always win. With prefix inference enabled, a prefix
+    /// matching exactly one argument resolves to it, and a prefix matching
+    /// several is an error.
+    fn find_long(&self, name: &str) -> Result<Option<usize>> {
+        if let Some(idx) = self.exact_long(name) {
+            return Ok(Some(idx));
+        }
+        if !self.infer_long || name.is_empty() {
+            return Ok(None);
+        }
+        let prefix = self.fold(name);
+
+        let mut candidates: Vec<(&str, usize)> = self
+            .long_map
+            .iter()
+            .filter(|(long, _)| self.fold(long).starts_with(prefix.as_ref()))
+            .map(|(long, &idx)| (long.as_str(), idx))
+            .collect();
+        candidates.sort();
+
+        let first = match candidates.first() {
+            Some(&(_, idx)) => idx,
+            None => return Ok(None),
+        };
+        if candidates.iter().all(|&(_, idx)| idx == first) {
+            return Ok(Some(first));
+        }
+
+        Err(Error::AmbiguousFlag {
+            flag: format!("--{}", name),
+            candidates: candidates
+                .iter()
+                .map(|(long, _)| format!("--{}", long))
+                .collect(),
+        })
+    }
+
+    /// Whether `token` is a long flag resolving to the arg called `name`,
+    /// through an alias, a unique prefix or a case-insensitive match
+    pub fn is_long_flag_for(&self, token: &str, name: &str) -> bool {
+        let Some(flag) = token.strip_prefix("--") else {
+            return false;
+        };
+        let flag = flag.split_once('=').map_or(flag, |(flag, _)| flag);
+        matches!(self.find_long(flag), Ok(Some(idx)) if self.args[idx].name == name)
+    }
+
+    /// Look up a long flag by its exact name (ignoring case if enabled)
+    fn exact_long(&self, name: &str) -> Option<usize> {
+        if self.ignore_case {
+            self.folded_long_map.get(&name.to_lowercase()).copied()
+        } else {
+            self.long_map.get(name).copied()
+        }
+    }
+
+    /// A flag name as compared: lowercased when ignoring case
+    fn fold<'s>(&self, name: &'s str) -> std::borrow::Cow<'s, str> {
+        if self.ignore_case {
+            name.to_lowercase().into()
+        } else {
+            name.into()
+        }
+    }
+
+    /// Resolve `--no-<name>` to the Bool arg it turns off, if any
+    ///
+    /// `full` is the whole flag name, `no-` included. Exact matches beat
+    /// prefixes, and a flag actually named `no-...` beats a negation at the
+    /// same level: exact `full`, exact `name`, prefix of `full`, prefix of
+    /// `name`. When `full` wins, the token is left to normal flag handling.
+    fn find_negated(&self, full: &str, name: &str) -> Result<Option<usize>> {
+        let idx = if self.exact_long(full).is_some() {
+            return Ok(None);
+        } else if let Some(idx) = self.exact_long(name) {
+            idx
+        } else if !matches!(self.find_long(full), Ok(None)) {
+            return Ok(None);
+        } else {
+            match self.find_long(name)? {
+                Some(idx) => idx,
+                None => return Ok(None),
+            }
+        };
+        Ok(Some(idx).filter(|&idx| self.args[idx].arg_type == ArgType::Bool))
+    }
+
     pub fn parse(&self, args: Vec<String>) -> Result<Matches> {
+        let total = args.len();
         let mut matches = Matches::new();
         let mut args_iter = args.into_iter().peekable();
+
+        // Errors point at the last token taken, i.e. the one being handled
+        if let Err(e) = self.parse_tokens(&mut args_iter, &mut matches) {
+            let position = total.saturating_sub(args_iter.len() + 1);
+            return Err(e.at_position(position));
+        }
+
+        self.check_groups(&matches)?;
+
+        for name in matches.values.keys() {
+            matches.sources.insert(name.clone(), ValueSource::Cli);
+        }
+        mark_fallbacks(&mut matches);
+
+        Ok(matches)
+    }
+
+    /// Match each token to a flag, value or positional
+    fn parse_tokens(
+        &self,
+        args_iter: &mut std::iter::Peekable<std::vec::IntoIter<String>>,
+        matches: &mut Matches,
+    ) -> Result<()> {
         let mut positional_index = 0;
         let mut seen_double_dash = false;
+        let total = args_iter.len();
+        // Index of the token just taken, given how many are left
+        let current = |left: usize| total - left - 1;
 
         while let Some(arg) = args_iter.next() {
             // After --, everything is a remaining argument
@@ -70,36 +344,71 @@ impl<'a> ArgParser<'a> {
             if let Some(rest) = arg.strip_prefix("--") {
                 // Check for --no-flag syntax
                 if let Some(flag_name) = rest.strip_prefix("no-")
-                    && let Some(&idx) = self.long_map.get(flag_name) {
-                        let arg_def = &self.args[idx];
-                        if arg_def.arg_type == ArgType::Bool {
-                            matches
-                                .values
-                                .insert(arg_def.name.clone(), Value::Boolean(false));
-                            continue;
-                        }
-                    }
+                    && let Some(idx) = self.find_negated(rest, flag_name)?
+                {
+                    matches
+                        .values
+                        .insert(self.args[idx].name.clone(), Value::Boolean(false));
+                    continue;
+                }
 
                 // Check for --flag=value syntax
-                let (flag_name, inline_value) = if let Some(pos) = rest.find('=') {
+                let (flag_name, inline_value) = if self.equals_syntax
+                    && let Some(pos) = rest.find('=')
+                {
                     (&rest[..pos], Some(&rest[pos + 1..]))
                 } else {
                     (rest, None)
                 };
 
-                if let Some(&idx) = self.long_map.get(flag_name) {
-                    self.handle_flag(idx, inline_value, &mut args_iter, &mut matches)?;
+                if let Some(idx) = self.find_long(flag_name)? {
+                    let at = current(args_iter.len());
+                    self.handle_flag(idx, inline_value, at, args_iter, matches)?;
+                } else if let Some(target) = &self.capture_unknown {
+                    // Take the next token as the value unless it looks like a flag
+                    let value = match inline_value {
+                        Some(v) => Value::String(v.to_string()),
+                        None => match args_iter
+                            .next_if(|next| !next.starts_with('-') || self.is_negative_number(next))
+                        {
+                            Some(v) => Value::String(v),
+                            None => Value::Boolean(true),
+                        },
+                    };
+                    let map = matches
+                        .values
+                        .entry(target.clone())
+                        .or_insert_with(|| Value::Table(Table::new()));
+                    if let Value::Table(t) = map {
+                        t.insert(flag_name.to_string(), value);
+                    }
+                } else if self.ignore_unknown {
+                    matches.remaining.push(arg.clone());
+                } else if let Some(suggestion) = self.suggest_long(flag_name) {
+                    return Err(Error::UnknownFlagSuggest {
+                        flag: format!("--{}", flag_name),
+                        suggestion: format!("--{}", suggestion),
+                        position: None,
+                    });
                 } else {
                     return Err(Error::UnknownFlag {
                         flag: format!("--{}", flag_name),
+                        position: None,
                     });
                 }
             }
+            // Negative number (-5, -0.5, -.5, -3.14e2) as a positional
+            else if self.is_negative_number(&arg) {
+                let origin = Origin::Token(current(args_iter.len()));
+                self.handle_positional(arg, positional_index, origin, matches)?;
+                positional_index += 1;
+            }
             // Short flag(s)
             else if let Some(rest) = arg.strip_prefix('-') {
                 if rest.is_empty() {
                     // Bare "-" is treated as a positional
-                    self.handle_positional("-".to_string(), positional_index, &mut matches)?;
+                    let origin = Origin::Token(current(args_iter.len()));
+                    self.handle_positional("-".to_string(), positional_index, origin, matches)?;
                     positional_index += 1;
                     continue;
                 }
@@ -115,61 +424,118 @@ impl<'a> ArgParser<'a> {
 
                         match arg_def.arg_type {
                             ArgType::Bool => {
+                                // A trailing '-' turns the flag off (-t- = --no-tls)
+                                let negated = chars.get(i + 1) == Some(&'-');
                                 matches
                                     .values
-                                    .insert(arg_def.name.clone(), Value::Boolean(true));
-                                i += 1;
+                                    .insert(arg_def.name.clone(), Value::Boolean(!negated));
+                                i += if negated { 2 } else { 1 };
                             }
                             ArgType::Count => {
-                                let current = matches
-                                    .values
-                                    .get(&arg_def.name)
-                                    .and_then(|v| v.as_integer())
-                                    .unwrap_or(0);
-                                matches
-                                    .values
-                                    .insert(arg_def.name.clone(), Value::Integer(current + 1));
+                                self.increment_count(arg_def, matches)?;
                                 i += 1;
                             }
                             _ => {
                                 // Value-taking flag
                                 // Check if the rest of the chars form the value
                                 if i + 1 < chars.len() {
-                                    let value: String = chars[i + 1..].iter().collect();
-                                    self.set_value(idx, &value, &mut matches)?;
+                                    let mut value: String = chars[i + 1..].iter().collect();
+                                    // -o=value means the same as --out=value
+                                    if self.equals_syntax && value.starts_with('=') {
+                                        value.remove(0);
+                                    }
+                                    let origin = Origin::Attached(current(args_iter.len()));
+                                    self.set_values(idx, value, origin, args_iter, matches)?;
                                     break;
                                 } else {
+                                    self.check_detached_value(arg_def)?;
                                     // Value is in the next argument
                                     let value =
                                         args_iter.next().ok_or_else(|| Error::MissingValue {
                                             name: arg_def.name.clone(),
                                         })?;
-                                    self.set_value(idx, &value, &mut matches)?;
+                                    let origin = Origin::Token(current(args_iter.len()));
+                                    self.set_values(idx, value, origin, args_iter, matches)?;
                                     i += 1;
                                 }
                             }
                         }
+                    } else if self.ignore_unknown {
+                        // Pass the unknown char through, keep processing the cluster
+                        matches.remaining.push(format!("-{}", c));
+                        i += 1;
                     } else {
                         return Err(Error::UnknownFlag {
                             flag: format!("-{}", c),
+                            position: None,
                         });
                     }
                 }
             }
+            // Plus toggles: +x (or a cluster like +xy) turns Bool flags off
+            else if self.plus_toggles
+                && let Some(rest) = arg.strip_prefix('+')
+                && !rest.is_empty()
+            {
+                for c in rest.chars() {
+                    match self.short_map.get(&c).map(|&idx| &self.args[idx]) {
+                        Some(arg_def) if arg_def.arg_type == ArgType::Bool => {
+                            matches
+                                .values
+                                .insert(arg_def.name.clone(), Value::Boolean(false));
+                        }
+                        _ => {
+                            return Err(Error::UnknownFlag {
+                                flag: format!("+{}", c),
+                                position: None,
+                            });
+                        }
+                    }
+                }
+            }
+            // Defined subcommand: the child parser takes all following tokens
+            else if positional_index == 0
+                && let Some(name) = self.find_subcommand(&arg)
+            {
+                matches.pending_subcommand = Some((name.to_string(), args_iter.collect()));
+                break;
+            }
+            // External subcommand: hand off this and all following tokens
+            else if self.external_subcommands
+                && positional_index == 0
+                && self.positionals.is_empty()
+            {
+                matches.external_subcommand = Some((arg, args_iter.collect()));
+                break;
+            }
+            // Nothing else could take this token
+            else if !self.subcommands.is_empty()
+                && positional_index == 0
+                && self.positionals.is_empty()
+            {
+                return Err(Error::UnknownSubcommand {
+                    suggestion: self.suggest_subcommand(&arg).map(str::to_string),
+                    name: arg,
+                    available: self.subcommands.iter().map(|(n, _)| n.clone()).collect(),
+                });
+            }
             // Positional argument
             else {
-                self.handle_positional(arg, positional_index, &mut matches)?;
+                let origin = Origin::Token(current(args_iter.len()));
+                self.handle_positional(arg, positional_index, origin, matches)?;
                 positional_index += 1;
             }
         }
 
-        Ok(matches)
+        matches.positional_count = positional_index;
+        Ok(())
     }
 
     fn handle_flag(
         &self,
         idx: usize,
         inline_value: Option<&str>,
+        at: usize,
         args_iter: &mut std::iter::Peekable<std::vec::IntoIter<String>>,
         matches: &mut Matches,
     ) -> Result<()> {
@@ -179,8 +545,7 @@ impl<'a> ArgParser<'a> {
             ArgType::Bool => {
                 if let Some(inline_val) = inline_value {
                     // --flag=value for bool - parse the value
-                    let lower = inline_val.to_lowercase();
-                    let b = lower == "true" || lower == "1" || lower == "yes";
+                    let b = crate::parse_bool(inline_val).unwrap_or(false);
                     matches
                         .values
                         .insert(arg_def.name.clone(), Value::Boolean(b));
@@ -190,64 +555,87 @@ impl<'a> ArgParser<'a> {
                         .insert(arg_def.name.clone(), Value::Boolean(true));
                 }
             }
-            ArgType::Count => {
-                let current = matches
-                    .values
-                    .get(&arg_def.name)
-                    .and_then(|v| v.as_integer())
-                    .unwrap_or(0);
-                matches
-                    .values
-                    .insert(arg_def.name.clone(), Value::Integer(current + 1));
-            }
+            ArgType::Count => self.increment_count(arg_def, matches)?,
             _ => {
-                let value = if let Some(v) = inline_value {
-                    v.to_string()
+                let (value, origin) = if let Some(v) = inline_value {
+                    (v.to_string(), Origin::Attached(at))
                 } else {
-                    args_iter.next().ok_or_else(|| Error::MissingValue {
+                    self.check_detached_value(arg_def)?;
+                    let value = args_iter.next().ok_or_else(|| Error::MissingValue {
                         name: arg_def.name.clone(),
-                    })?
+                    })?;
+                    (value, Origin::Token(at + 1))
                 };
-                self.set_value(idx, &value, matches)?;
+                self.set_values(idx, value, origin, args_iter, matches)?;
             }
         }
 
         Ok(())
     }
 
-    fn handle_positional(&self, value: String, index: usize, matches: &mut Matches) -> Result<()> {
+    /// Fail if `arg_def` may only take an attached value
+    fn check_detached_value(&self, arg_def: &Arg) -> Result<()> {
+        if self.require_equals.contains(&arg_def.name) {
+            let flag = match (&arg_def.long, arg_def.short) {
+                (Some(l), _) => format!("--{}", l),
+                (None, Some(c)) => format!("-{}", c),
+                (None, None) => arg_def.name.clone(),
+            };
+            return Err(Error::RequiresEquals { flag });
+        }
+        Ok(())
+    }
+
+    /// Count one more occurrence of a Count flag, enforcing its `max_count`
+    fn increment_count(&self, arg_def: &Arg, matches: &mut Matches) -> Result<()> {
+        let count = matches
+            .values
+            .get(&arg_def.name)
+            .and_then(|v| v.as_integer())
+            .unwrap_or(0)
+            + 1;
+        if let Some(max) = arg_def.max_count
+            && count > max
+        {
+            return Err(Error::CountExceeded {
+                name: arg_def.name.clone(),
+                max,
+            });
+        }
+        matches
+            .values
+            .insert(arg_def.name.clone(), Value::Integer(count));
+        Ok(())
+    }
+
+    fn handle_positional(
+        &self,
+        value: String,
+        index: usize,
+        origin: Origin,
+        matches: &mut Matches,
+    ) -> Result<()> {
         // Find the appropriate positional argument
         if index < self.positionals.len() {
             let arg_idx = self.positionals[index];
             let arg_def = &self.args[arg_idx];
+            let element = element_count(matches, &arg_def.name);
 
             if arg_def.variadic {
-                // Variadic: add to array
-                let arr = matches
-                    .values
-                    .entry(arg_def.name.clone())
-                    .or_insert_with(|| Value::Array(Array::new()));
-                if let Value::Array(a) = arr {
-                    a.push(self.parse_value_as_type(&value, ArgType::String)?);
-                }
+                self.push_variadic(arg_def, &value, matches)?;
             } else {
-                matches.values.insert(
-                    arg_def.name.clone(),
-                    self.parse_value_as_type(&value, arg_def.arg_type)?,
-                );
+                let parsed = self.parse_arg_value(arg_def, &value, matches)?;
+                matches.values.insert(arg_def.name.clone(), parsed);
             }
+            self.keep_original(&arg_def.name, origin, element, matches);
         } else {
             // Check if the last positional is variadic
             if let Some(&last_idx) = self.positionals.last() {
                 let last_arg = &self.args[last_idx];
                 if last_arg.variadic {
-                    let arr = matches
-                        .values
-                        .entry(last_arg.name.clone())
-                        .or_insert_with(|| Value::Array(Array::new()));
-                    if let Value::Array(a) = arr {
-                        a.push(self.parse_value_as_type(&value, ArgType::String)?);
-                    }
+                    let element = element_count(matches, &last_arg.name);
+                    self.push_variadic(last_arg, &value, matches)?;
+                    self.keep_original(&last_arg.name, origin, element, matches);
                     return Ok(());
                 }
             }
@@ -261,7 +649,274 @@ impl<'a> ArgParser<'a> {
         Ok(())
     }
 
-    fn set_value(&self, idx: usize, value: &str, matches: &mut Matches) -> Result<()> {
+    /// Add a value to a variadic positional
+    ///
+    /// For positional groups, values fill the members of the last record in
+    /// order, starting a new record once it is complete. Each value is
+    /// checked like a flag value for the arg (or group member) it fills.
+    fn push_variadic(&self, arg_def: &Arg, value: &str, matches: &mut Matches) -> Result<()> {
+        if arg_def.group.is_empty() {
+            let element = self.parse_arg_value(arg_def, value, matches)?;
+            if let Value::Array(a) = matches
+                .values
+                .entry(arg_def.name.clone())
+                .or_insert_with(|| Value::Array(Array::new()))
+                && !(arg_def.dedup && a.iter().any(|v| *v == element))
+            {
+                a.push(element);
+            }
+            return Ok(());
+        }
+
+        let filled = match matches.values.get(&arg_def.name).and_then(|v| v.as_array()) {
+            Some(a) => a
+                .iter()
+                .last()
+                .and_then(|v| v.as_table())
+                .map_or(0, |t| t.len()),
+            None => 0,
+        };
+        let position = filled % arg_def.group.len();
+        let member = &arg_def.group[position];
+        let element = self.parse_arg_value(member, value, matches)?;
+
+        let Value::Array(a) = matches
+            .values
+            .entry(arg_def.name.clone())
+            .or_insert_with(|| Value::Array(Array::new()))
+        else {
+            return Ok(());
+        };
+        if position == 0 {
+            a.push(Value::Table(Table::new()));
+        }
+        if let Some(Value::Table(record)) = a.get_mut(a.len() - 1) {
+            record.insert(member.name.clone(), element);
+        }
+        Ok(())
+    }
+
+    /// Check that every positional group ended on a complete record
+    fn check_groups(&self, matches: &Matches) -> Result<()> {
+        for &idx in &self.positionals {
+            let arg_def = &self.args[idx];
+            if arg_def.group.is_empty() {
+                continue;
+            }
+            let last = matches
+                .values
+                .get(&arg_def.name)
+                .and_then(|v| v.as_array())
+                .and_then(|a| a.iter().last())
+                .and_then(|v| v.as_table());
+            if let Some(record) = last
+                && record.len() < arg_def.group.len()
+            {
+                return Err(Error::PartialRecord {
+                    name: arg_def.name.clone(),
+                    expected: arg_def.group.len(),
+                    got: record.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a flag's value, taking further values for multi-value options
+    fn set_values(
+        &self,
+        idx: usize,
+        first: String,
+        origin: Origin,
+        args_iter: &mut std::iter::Peekable<std::vec::IntoIter<String>>,
+        matches: &mut Matches,
+    ) -> Result<()> {
+        let arg_def = &self.args[idx];
+        if arg_def.rest_of_line {
+            let mut line = first;
+            while let Some(next) = args_iter.next_if(|next| next != "--") {
+                line.push(' ');
+                line.push_str(&next);
+            }
+            return self.set_value(idx, &line, matches);
+        }
+        if arg_def.num_values <= 1 && !arg_def.multiple_values {
+            let element = element_count(matches, &arg_def.name);
+            self.set_value(idx, &first, matches)?;
+            self.keep_original(&arg_def.name, origin, element, matches);
+            return Ok(());
+        }
+
+        let mut values = vec![first];
+        if arg_def.multiple_values {
+            while let Some(next) = args_iter.next_if(|next| !self.is_flag_boundary(next)) {
+                values.push(next);
+            }
+        } else {
+            while values.len() < arg_def.num_values {
+                values.push(args_iter.next().ok_or_else(|| Error::MissingValue {
+                    name: arg_def.name.clone(),
+                })?);
+            }
+        }
+
+        // Further values are the tokens right after the first one's
+        let (Origin::Token(at) | Origin::Attached(at)) = origin;
+        let origins = (0..values.len()).map(|i| match i {
+            0 => origin,
+            i => Origin::Token(at + i),
+        });
+
+        if arg_def.arg_type == ArgType::Array {
+            for (value, origin) in values.iter().zip(origins) {
+                let element = element_count(matches, &arg_def.name);
+                self.set_value(idx, value, matches)?;
+                self.keep_original(&arg_def.name, origin, element, matches);
+            }
+            return Ok(());
+        }
+
+        let existing = matches.values.remove(&arg_def.name);
+        let mut arr = match existing {
+            // Repeating a greedy flag appends to what it collected so far
+            Some(Value::Array(a)) if arg_def.multiple_values => a,
+            Some(_) => {
+                return Err(Error::DuplicateValue {
+                    name: arg_def.name.clone(),
+                });
+            }
+            None => Array::new(),
+        };
+        let start = arr.len();
+        for value in &values {
+            let element = self.parse_arg_value(arg_def, value, matches)?;
+            arr.push(element);
+        }
+        matches
+            .values
+            .insert(arg_def.name.clone(), Value::Array(arr));
+        for (i, origin) in origins.enumerate() {
+            self.keep_original(&arg_def.name, origin, start + i, matches);
+        }
+        Ok(())
+    }
+
+    /// Keep the original `OsString` of a value taken from a non-UTF-8 token
+    ///
+    /// `element` is where the value landed if the arg holds an array. Nothing
+    /// is kept if the stored value isn't the token's text, e.g. when lenient
+    /// parsing replaced it with a default.
+    fn keep_original(&self, name: &str, origin: Origin, element: usize, matches: &mut Matches) {
+        let original = match origin {
+            Origin::Token(at) => self.os_args.get(&at).cloned(),
+            Origin::Attached(at) => self.os_args.get(&at).and_then(|t| attached_os_value(t)),
+        };
+        let Some(original) = original else {
+            return;
+        };
+        let lossy = original.to_string_lossy();
+        match matches.values.get(name) {
+            Some(Value::String(s)) if *s == lossy => {
+                matches.os_values.insert(name.to_string(), original);
+            }
+            Some(Value::Array(a)) if a.get(element).and_then(|v| v.as_str()) == Some(&lossy) => {
+                matches
+                    .os_elements
+                    .entry(name.to_string())
+                    .or_default()
+                    .insert(element, original);
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether a token is a negative number rather than a short flag cluster
+    ///
+    /// A defined digit short flag (`-1`) keeps its flag meaning.
+    fn is_negative_number(&self, token: &str) -> bool {
+        crate::is_negative_number(token)
+            && token[1..]
+                .chars()
+                .next()
+                .is_some_and(|c| !self.short_map.contains_key(&c))
+    }
+
+    /// Whether a token ends a greedy value list: `--` or a defined flag
+    fn is_flag_boundary(&self, token: &str) -> bool {
+        if token == "--" {
+            return true;
+        }
+        if let Some(rest) = token.strip_prefix("--") {
+            let name = rest.split('=').next().unwrap_or(rest);
+            let defined = |n: &str| !matches!(self.find_long(n), Ok(None));
+            return defined(name) || name.strip_prefix("no-").is_some_and(defined);
+        }
+        if let Some(rest) = token.strip_prefix('-') {
+            return rest
+                .chars()
+                .next()
+                .is_some_and(|c| self.short_map.contains_key(&c));
+        }
+        false
+    }
+
+    /// Set a value read from an environment variable
+    ///
+    /// Array values are split on the arg's delimiter, defaulting to a comma.
+    pub fn set_env_value(&self, idx: usize, value: &str, matches: &mut Matches) -> Result<()> {
+        let arg_def = &self.args[idx];
+        if arg_def.arg_type == ArgType::KeyValue {
+            let delim = arg_def.value_delimiter.unwrap_or(',');
+            for piece in split_quoted(value, delim) {
+                self.set_value(idx, &piece, matches)?;
+            }
+        } else if arg_def.arg_type == ArgType::Array {
+            let delim = arg_def.value_delimiter.unwrap_or(',');
+            let pieces: Vec<String> = split_quoted(value, delim)
+                .into_iter()
+                .filter(|p| !p.is_empty())
+                .collect();
+            return self.set_env_elements(idx, &pieces, matches);
+        } else {
+            let parsed = self.parse_arg_value(arg_def, value, matches)?;
+            matches.values.insert(arg_def.name.clone(), parsed);
+        }
+        matches
+            .sources
+            .insert(arg_def.name.clone(), ValueSource::Env);
+        mark_fallbacks(matches);
+        Ok(())
+    }
+
+    /// Set an array arg from separate environment values, one per element
+    ///
+    /// Used for indexed variables (`VAR_0`, `VAR_1`, ...), whose values are
+    /// not split.
+    pub fn set_env_elements(
+        &self,
+        idx: usize,
+        elements: &[String],
+        matches: &mut Matches,
+    ) -> Result<()> {
+        let arg_def = &self.args[idx];
+        let mut arr = Array::new();
+        for piece in elements {
+            let element = Value::String(self.checked_raw(arg_def, piece)?);
+            arg_def.check_value(&element)?;
+            if !(arg_def.dedup && arr.iter().any(|v| *v == element)) {
+                arr.push(element);
+            }
+        }
+        matches
+            .values
+            .insert(arg_def.name.clone(), Value::Array(arr));
+        matches
+            .sources
+            .insert(arg_def.name.clone(), ValueSource::Env);
+        Ok(())
+    }
+
+    pub fn set_value(&self, idx: usize, value: &str, matches: &mut Matches) -> Result<()> {
         let arg_def = &self.args[idx];
 
         match arg_def.arg_type {
@@ -272,7 +927,39 @@ impl<'a> ArgParser<'a> {
                     .entry(arg_def.name.clone())
                     .or_insert_with(|| Value::Array(Array::new()));
                 if let Value::Array(a) = arr {
-                    a.push(self.parse_value_as_type(value, ArgType::String)?);
+                    // Empty segments (e.g. from a trailing comma) are skipped
+                    let pieces = match arg_def.value_delimiter {
+                        Some(delim) => split_quoted(value, delim)
+                            .into_iter()
+                            .filter(|p| !p.is_empty())
+                            .collect(),
+                        None => vec![value.to_string()],
+                    };
+                    for piece in pieces {
+                        let element = Value::String(self.checked_raw(arg_def, &piece)?);
+                        arg_def.check_value(&element)?;
+                        if !(arg_def.dedup && a.iter().any(|v| *v == element)) {
+                            a.push(element);
+                        }
+                    }
+                }
+            }
+            ArgType::KeyValue => {
+                // Pairs accumulate into a table, split on the first separator
+                let sep = arg_def.pair_separator;
+                let Some((key, val)) = value.split_once(sep) else {
+                    return Err(Error::MissingSeparator {
+                        name: arg_def.name.clone(),
+                        value: value.to_string(),
+                        separator: sep,
+                    });
+                };
+                let map = matches
+                    .values
+                    .entry(arg_def.name.clone())
+                    .or_insert_with(|| Value::Table(Table::new()));
+                if let Value::Table(t) = map {
+                    t.insert(key.to_string(), Value::String(val.to_string()));
                 }
             }
             _ => {
@@ -283,56 +970,339 @@ impl<'a> ArgParser<'a> {
                         name: arg_def.name.clone(),
                     });
                 }
-                matches.values.insert(
-                    arg_def.name.clone(),
-                    self.parse_value_as_type(value, arg_def.arg_type)?,
-                );
+                let parsed = self.parse_arg_value(arg_def, value, matches)?;
+                matches.values.insert(arg_def.name.clone(), parsed);
             }
         }
 
         Ok(())
     }
 
-    fn parse_value_as_type(&self, value: &str, arg_type: ArgType) -> Result<Value> {
-        match arg_type {
-            ArgType::String => Ok(Value::String(value.to_string())),
-            ArgType::Integer => {
-                value
-                    .parse::<i64>()
-                    .map(Value::Integer)
-                    .map_err(|_| Error::InvalidValue {
-                        name: String::new(),
-                        value: value.to_string(),
-                        expected: "an integer",
-                    })
+    /// Check a raw value against the arg's allowed values and pattern
+    ///
+    /// When ignoring case, a value matching a possible value in another case
+    /// is returned with the possible value's casing.
+    fn checked_raw(&self, arg_def: &Arg, value: &str) -> Result<String> {
+        let canonical = arg_def
+            .possible_values
+            .iter()
+            .find(|p| self.ignore_case && p.to_lowercase() == value.to_lowercase())
+            .map_or(value, String::as_str);
+        arg_def.check_raw(canonical)?;
+        Ok(canonical.to_string())
+    }
+
+    /// Parse a value for a specific argument, applying its per-arg settings
+    ///
+    /// In lenient mode a value that fails to parse or validate is replaced by
+    /// the arg's default, if it has one, and a warning is recorded.
+    fn parse_arg_value(&self, arg_def: &Arg, value: &str, matches: &mut Matches) -> Result<Value> {
+        let result = self
+            .checked_raw(arg_def, value)
+            .and_then(|raw| self.parse_typed_value(arg_def, &raw))
+            .and_then(|parsed| arg_def.check_value(&parsed).map(|_| parsed));
+
+        let default = match &arg_def.default {
+            Some(default) if self.lenient => default,
+            _ => return result,
+        };
+        let reason = match result {
+            Ok(parsed) => return Ok(parsed),
+            Err(Error::InvalidValue { expected, .. }) => format!("expected {}", expected),
+            Err(Error::Validation { message, .. }) => message,
+            Err(Error::OutOfRange { min, max, .. }) => {
+                format!("must be between {} and {}", min, max)
             }
+            Err(e) => return Err(e),
+        };
+        matches.warnings.push(Warning::InvalidValue {
+            arg: arg_def.name.clone(),
+            value: value.to_string(),
+            reason,
+        });
+        Ok(default.clone())
+    }
+
+    /// Parse a value as the arg's type
+    fn parse_typed_value(&self, arg_def: &Arg, value: &str) -> Result<Value> {
+        // A bare IP gets the default port, if one is set
+        if arg_def.arg_type == ArgType::SocketAddr
+            && let Some(port) = arg_def.default_port
+            && let Ok(ip) = value.parse::<IpAddr>()
+        {
+            return Ok(Value::String(SocketAddr::new(ip, port).to_string()));
+        }
+
+        // Lenient IP args also accept hostnames
+        if arg_def.arg_type == ArgType::IpAddr && arg_def.allow_hostname && is_hostname(value) {
+            return Ok(Value::String(value.to_string()));
+        }
+
+        self.parse_value_as_type(&arg_def.name, value, arg_def.arg_type)
+    }
+
+    fn parse_value_as_type(&self, name: &str, value: &str, arg_type: ArgType) -> Result<Value> {
+        match arg_type {
+            ArgType::String | ArgType::Path => Ok(Value::String(value.to_string())),
+            ArgType::Integer => parse_integer(name, value, false),
+            ArgType::UnsignedInteger => parse_integer(name, value, true),
             ArgType::Float => {
                 value
                     .parse::<f64>()
                     .map(Value::Float)
                     .map_err(|_| Error::InvalidValue {
-                        name: String::new(),
+                        name: name.to_string(),
                         value: value.to_string(),
                         expected: "a number",
+                        position: None,
                     })
             }
-            ArgType::Bool => {
-                let lower = value.to_lowercase();
-                Ok(Value::Boolean(
-                    lower == "true" || lower == "1" || lower == "yes",
-                ))
+            ArgType::Bool => Ok(Value::Boolean(crate::parse_bool(value).unwrap_or(false))),
+            ArgType::Count => parse_integer(name, value, false),
+            ArgType::Bytes => {
+                parse_bytes(value)
+                    .map(Value::Integer)
+                    .ok_or_else(|| Error::InvalidValue {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                        expected: "a byte size",
+                        position: None,
+                    })
             }
-            ArgType::Count => {
-                value
-                    .parse::<i64>()
+            ArgType::Duration => {
+                parse_duration(value)
                     .map(Value::Integer)
+                    .ok_or_else(|| Error::InvalidValue {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                        expected: "a duration",
+                        position: None,
+                    })
+            }
+            ArgType::Array | ArgType::KeyValue => Ok(Value::String(value.to_string())),
+            ArgType::IpAddr => value
+                .parse::<IpAddr>()
+                .map(|ip| Value::String(ip.to_string()))
+                .map_err(|_| Error::InvalidValue {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    expected: "an IP address",
+                    position: None,
+                }),
+            ArgType::SocketAddr => {
+                // ":8080" binds all interfaces
+                let addr = if value.starts_with(':') && !value.starts_with("::") {
+                    format!("0.0.0.0{}", value)
+                } else {
+                    value.to_string()
+                };
+                addr.parse::<SocketAddr>()
+                    .map(|a| Value::String(a.to_string()))
                     .map_err(|_| Error::InvalidValue {
-                        name: String::new(),
+                        name: name.to_string(),
                         value: value.to_string(),
-                        expected: "an integer",
+                        expected: "a socket address (host:port)",
+                        position: None,
                     })
             }
-            ArgType::Array => Ok(Value::String(value.to_string())),
         }
     }
 }
+
+/// Mark the values lenient parsing replaced with their arg's default
+///
+/// Arrays are left alone, as only some of their elements were replaced.
+fn mark_fallbacks(matches: &mut Matches) {
+    for warning in &matches.warnings {
+        if let Warning::InvalidValue { arg, .. } = warning
+            && matches.values.get(arg).is_some_and(|v| !v.is_array())
+        {
+            matches.sources.insert(arg.clone(), ValueSource::Default);
+        }
+    }
+}
+
+/// Number of elements an array arg holds so far (0 if it has no value yet)
+fn element_count(matches: &Matches, name: &str) -> usize {
+    matches
+        .values
+        .get(name)
+        .and_then(|v| v.as_array())
+        .map_or(0, |a| a.len())
+}
+
+/// Split `value` on `delim`, honoring single and double quotes
+///
+/// Quotes are removed, and a backslash takes the next character literally.
+fn split_quoted(value: &str, delim: char) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            '"' | '\'' if quote.is_none() => quote = Some(c),
+            _ if quote == Some(c) => quote = None,
+            _ if c == delim && quote.is_none() => pieces.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    pieces.push(current);
+
+    pieces
+}
+
+/// Parse an integer, telling overflow and sign errors apart from non-numbers
+///
+/// Accepts `0x`, `0o` and `0b` prefixes and `_` digit separators
+/// (`0xFF`, `-0b1010`, `1_000_000`).
+fn parse_integer(name: &str, value: &str, unsigned: bool) -> Result<Value> {
+    let invalid = |expected| Error::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+        expected,
+        position: None,
+    };
+
+    let cleaned = value.replace('_', "");
+    let (sign, unsigned_part) = match cleaned.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", cleaned.strip_prefix('+').unwrap_or(&cleaned)),
+    };
+    let (radix, digits) = match unsigned_part.get(..2) {
+        Some("0x" | "0X") => (16, &unsigned_part[2..]),
+        Some("0o" | "0O") => (8, &unsigned_part[2..]),
+        Some("0b" | "0B") => (2, &unsigned_part[2..]),
+        _ => (10, unsigned_part),
+    };
+    // from_str_radix would accept a second sign after the prefix ("0x-1")
+    if digits.starts_with(['+', '-']) || value.starts_with('_') || value.ends_with('_') {
+        return Err(invalid("an integer"));
+    }
+
+    match i64::from_str_radix(&format!("{}{}", sign, digits), radix) {
+        Ok(i) if unsigned && i < 0 => Err(invalid("a non-negative integer")),
+        Ok(i) => Ok(Value::Integer(i)),
+        Err(e) => match e.kind() {
+            IntErrorKind::PosOverflow => {
+                Err(invalid("an integer no larger than 9223372036854775807"))
+            }
+            IntErrorKind::NegOverflow if unsigned => Err(invalid("a non-negative integer")),
+            IntErrorKind::NegOverflow => {
+                Err(invalid("an integer no smaller than -9223372036854775808"))
+            }
+            _ if unsigned => Err(invalid("a non-negative integer")),
+            _ => Err(invalid("an integer")),
+        },
+    }
+}
+
+/// Parse a byte size: a number with an optional unit, in any case
+///
+/// `KB`, `MB`, `GB` and `TB` are powers of 1000; `KiB`, `MiB`, `GiB`, `TiB`
+/// and the bare `K`, `M`, `G`, `T` are powers of 1024. No unit (or `B`)
+/// means bytes. Fractions are allowed and rounded down (`1.5K` = 1536).
+fn parse_bytes(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: i64 = match unit.trim_start().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_i64.pow(2),
+        "gb" => 1000_i64.pow(3),
+        "tb" => 1000_i64.pow(4),
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        _ => return None,
+    };
+
+    if let Ok(n) = number.parse::<i64>() {
+        return n.checked_mul(multiplier);
+    }
+    let n = number.parse::<f64>().ok()?;
+    let bytes = (n * multiplier as f64).floor();
+    (bytes.is_finite() && bytes < i64::MAX as f64).then_some(bytes as i64)
+}
+
+/// Parse a duration into milliseconds
+///
+/// Takes one or more number/unit pairs, summed (`1h30m`). Units are `ms`,
+/// `s`, `m`, `h` and `d`; a lone number with no unit is seconds, but every
+/// pair in a compound needs its unit (`1h30` is rejected). Fractions are
+/// allowed (`1.5s`, `1.5`).
+pub fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(secs) = value.parse::<i64>() {
+        return secs.checked_mul(1000);
+    }
+    if value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        let millis = value.parse::<f64>().ok()? * 1000.0;
+        return (millis < i64::MAX as f64).then_some(millis.round() as i64);
+    }
+
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let millis = match &rest[..unit_len] {
+            "ms" => 1.0,
+            "s" => 1000.0,
+            "m" => 60_000.0,
+            "h" => 3_600_000.0,
+            "d" => 86_400_000.0,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total += number * millis;
+    }
+    (total < i64::MAX as f64).then_some(total.round() as i64)
+}
+
+/// Levenshtein distance between two strings, counted in chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Check that a string is a valid DNS hostname (RFC 1123)
+fn is_hostname(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 253
+        && s.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
@@ -0,0 +1,63 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Returns true if stdin is attached to a terminal
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Prompt on stderr and read a single line from stdin
+///
+/// If `secret` is set, terminal echo is disabled while reading. If echo
+/// can't be disabled, this fails before prompting rather than show the
+/// secret as it is typed.
+pub fn read_line(prompt: &str, secret: bool) -> io::Result<String> {
+    if secret {
+        set_echo(false)?;
+    }
+
+    let mut stderr = io::stderr();
+    let mut line = String::new();
+    let result = write!(stderr, "{}", prompt)
+        .and_then(|_| stderr.flush())
+        .and_then(|_| io::stdin().lock().read_line(&mut line));
+
+    if secret {
+        // Restore echo even if reading failed
+        let restored = set_echo(true);
+        // The user's newline was not echoed
+        writeln!(stderr)?;
+        restored?;
+    }
+
+    result?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Toggle terminal echo for stdin
+#[cfg(unix)]
+fn set_echo(enabled: bool) -> io::Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mode = if enabled { "echo" } else { "-echo" };
+    let status = Command::new("stty")
+        .arg(mode)
+        .stdin(Stdio::inherit())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "stty {} failed ({})",
+            mode, status
+        )))
+    }
+}
+
+/// Toggle terminal echo for stdin (unsupported on this platform)
+#[cfg(not(unix))]
+fn set_echo(_enabled: bool) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "cannot disable terminal echo for secret input on this platform",
+    ))
+}
@@ -0,0 +1,210 @@
+use stoml::{Table, Value};
+
+use crate::{Arg, ArgType, Args, Error, Result};
+
+/// Every type, in the order listed in spec files
+const SPEC_TYPES: [ArgType; 13] = [
+    ArgType::String,
+    ArgType::Integer,
+    ArgType::UnsignedInteger,
+    ArgType::Float,
+    ArgType::Bool,
+    ArgType::Array,
+    ArgType::Count,
+    ArgType::SocketAddr,
+    ArgType::IpAddr,
+    ArgType::KeyValue,
+    ArgType::Path,
+    ArgType::Bytes,
+    ArgType::Duration,
+];
+
+impl ArgType {
+    /// The name used for this type in a spec file
+    fn spec_name(&self) -> &'static str {
+        match self {
+            ArgType::String => "string",
+            ArgType::Integer => "integer",
+            ArgType::UnsignedInteger => "unsigned",
+            ArgType::Float => "float",
+            ArgType::Bool => "bool",
+            ArgType::Array => "array",
+            ArgType::Count => "count",
+            ArgType::SocketAddr => "socket_addr",
+            ArgType::IpAddr => "ip_addr",
+            ArgType::KeyValue => "key_value",
+            ArgType::Path => "path",
+            ArgType::Bytes => "bytes",
+            ArgType::Duration => "duration",
+        }
+    }
+
+    /// The type with the given spec file name
+    fn from_spec_name(name: &str) -> Option<ArgType> {
+        SPEC_TYPES.into_iter().find(|t| t.spec_name() == name)
+    }
+}
+
+impl Args {
+    /// Export the argument definitions as a TOML spec
+    ///
+    /// The program's name, version and about are top-level keys, followed by
+    /// one `[[arg]]` table per argument with its name, short, long, type,
+    /// default, help, required and positional settings. Like
+    /// `generate_manpage`, auto flags are not included.
+    /// `Args::from_spec` reads it back.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # fn main() -> std::io::Result<()> {
+    /// # let parser = stoml_args::args("myapp");
+    /// std::fs::write("cli-spec.toml", parser.to_spec())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_spec(&self) -> String {
+        let mut spec = String::new();
+
+        spec.push_str(&format!("name = {}\n", quote(&self.name)));
+        if let Some(version) = &self.version {
+            spec.push_str(&format!("version = {}\n", quote(version)));
+        }
+        if let Some(about) = &self.about {
+            spec.push_str(&format!("about = {}\n", quote(about)));
+        }
+
+        for arg in &self.args {
+            spec.push_str("\n[[arg]]\n");
+            spec.push_str(&format!("name = {}\n", quote(&arg.name)));
+            if let Some(c) = arg.short {
+                spec.push_str(&format!("short = {}\n", quote(&c.to_string())));
+            }
+            if let Some(long) = &arg.long {
+                spec.push_str(&format!("long = {}\n", quote(long)));
+            }
+            spec.push_str(&format!("type = {}\n", quote(arg.arg_type.spec_name())));
+            if let Some(default) = &arg.default {
+                spec.push_str(&format!("default = {}\n", default));
+            }
+            if let Some(help) = &arg.help {
+                spec.push_str(&format!("help = {}\n", quote(help)));
+            }
+            spec.push_str(&format!("required = {}\n", arg.required));
+            if arg.positional {
+                spec.push_str("positional = true\n");
+            }
+        }
+
+        spec
+    }
+
+    /// Build a parser from a TOML spec in the format written by `to_spec`
+    ///
+    /// Unknown keys are ignored. A missing `name`, a mistyped key, or an
+    /// unknown `type` fails with `Error::InvalidDefinition`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use stoml_args::*;
+    /// # fn main() -> Result<()> {
+    /// let parser = Args::from_spec(&std::fs::read_to_string("cli-spec.toml")?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_spec(spec: &str) -> Result<Args> {
+        let table = stoml::parse(spec)?;
+        let name = spec_str(&table, "name")?
+            .ok_or_else(|| invalid_spec("missing top-level 'name'".to_string()))?;
+
+        let mut args = Args::new(name);
+        if let Some(version) = spec_str(&table, "version")? {
+            args = args.version(version);
+        }
+        if let Some(about) = spec_str(&table, "about")? {
+            args = args.about(about);
+        }
+
+        let entries = match table.get("arg") {
+            Some(Value::Array(entries)) => entries.iter().collect(),
+            Some(_) => return Err(invalid_spec("'arg' must be an array of tables".to_string())),
+            None => Vec::new(),
+        };
+        for entry in entries {
+            let Value::Table(entry) = entry else {
+                return Err(invalid_spec("'arg' must be an array of tables".to_string()));
+            };
+            args = args.arg(arg_from_spec(entry)?);
+        }
+        Ok(args)
+    }
+}
+
+/// Build one argument from its `[[arg]]` table
+fn arg_from_spec(entry: &Table) -> Result<Arg> {
+    let name =
+        spec_str(entry, "name")?.ok_or_else(|| invalid_spec("arg without 'name'".to_string()))?;
+    let mut arg = if spec_bool(entry, "positional")? {
+        Arg::positional(name)
+    } else {
+        Arg::new(name)
+    };
+
+    if let Some(short) = spec_str(entry, "short")? {
+        let mut chars = short.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => arg = arg.short(c),
+            _ => {
+                return Err(invalid_spec(format!(
+                    "arg '{}': short must be one character, got '{}'",
+                    arg.name, short
+                )));
+            }
+        }
+    }
+    if let Some(long) = spec_str(entry, "long")? {
+        arg = arg.long(long);
+    }
+    if let Some(type_name) = spec_str(entry, "type")? {
+        let arg_type = ArgType::from_spec_name(type_name).ok_or_else(|| {
+            invalid_spec(format!("arg '{}': unknown type '{}'", arg.name, type_name))
+        })?;
+        arg = arg.arg_type(arg_type);
+    }
+    if let Some(default) = entry.get("default") {
+        arg = arg.default(default.clone());
+    }
+    if let Some(help) = spec_str(entry, "help")? {
+        arg = arg.help(help);
+    }
+    if spec_bool(entry, "required")? {
+        arg = arg.required();
+    }
+    Ok(arg)
+}
+
+/// Read an optional string key from a spec table
+fn spec_str<'a>(table: &'a Table, key: &str) -> Result<Option<&'a str>> {
+    match table.get(key) {
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(_) => Err(invalid_spec(format!("'{}' must be a string", key))),
+        None => Ok(None),
+    }
+}
+
+/// Read an optional bool key from a spec table, `false` when absent
+fn spec_bool(table: &Table, key: &str) -> Result<bool> {
+    match table.get(key) {
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(_) => Err(invalid_spec(format!("'{}' must be a boolean", key))),
+        None => Ok(false),
+    }
+}
+
+fn invalid_spec(reason: String) -> Error {
+    Error::InvalidDefinition { reason }
+}
+
+/// Render a string as a TOML basic string
+fn quote(s: &str) -> String {
+    Value::String(s.to_string()).to_string()
+}
@@ -0,0 +1,132 @@
+//! Property tests for short flag clusters, on pseudo-random argv
+//!
+//! The same parser is fuzzed by `fuzz/fuzz_targets/short_clusters.rs`.
+
+use crate::{ArgType, Args, Value, arg, args};
+
+fn parser() -> Args {
+    args("fuzz")
+        .version("1.0")
+        .plus_minus_toggles()
+        .arg(arg("all").short('a').long("all").flag())
+        .arg(arg("brief").short('b').long("brief").flag())
+        .arg(
+            arg("verbose")
+                .short('v')
+                .long("verbose")
+                .count()
+                .max_count(5),
+        )
+        .arg(arg("out").short('o').long("out"))
+        .arg(arg("num").short('n').long("num").arg_type(ArgType::Integer))
+        .arg(
+            arg("include")
+                .short('I')
+                .long("include")
+                .arg_type(ArgType::Array)
+                .value_delimiter(','),
+        )
+        .arg(arg("define").short('D').arg_type(ArgType::KeyValue))
+        .arg(arg("point").short('p').value_names(&["x", "y"]))
+}
+
+/// xorshift64, so failures reproduce without a dependency
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+/// Pieces that exercise the cluster edge cases: values mid-cluster, `=`,
+/// trailing `-` negation, unknown and non-ASCII characters
+const PIECES: &[&str] = &[
+    "a", "b", "v", "o", "n", "I", "D", "p", "x", "=", "-", "+", "é", "1", ",", "k=v", "",
+];
+
+/// Tokens mixed in between clusters
+const TOKENS: &[&str] = &[
+    "--", "-", "+ab", "--out=x", "--no-all", "-5", "val", "", "=", "-o=",
+];
+
+fn random_argv(rng: &mut Rng) -> Vec<String> {
+    (0..rng.below(6))
+        .map(|_| {
+            if rng.below(3) == 0 {
+                return rng.pick(TOKENS).to_string();
+            }
+            let mut token = String::from("-");
+            for _ in 0..rng.below(5) {
+                token.push_str(rng.pick(PIECES));
+            }
+            token
+        })
+        .collect()
+}
+
+#[test]
+fn random_argv_never_panics() {
+    let mut rng = Rng(0x5eed_1234_abcd_ef01);
+    for _ in 0..20_000 {
+        let argv = random_argv(&mut rng);
+        match parser().parse_from(argv.clone()) {
+            Ok(m) => assert!(m.get_count("verbose") <= 5, "{:?}", argv),
+            Err(e) => {
+                assert!(!e.to_string().is_empty());
+                let _ = e.annotated(&argv);
+                if let Some(position) = e.position() {
+                    assert!(position < argv.len(), "{:?} points past {:?}", e, argv);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cluster_values_are_not_lost() {
+    let mut rng = Rng(0x0dd_c0ffee);
+    for _ in 0..2_000 {
+        let flags: String = (0..rng.below(4))
+            .map(|_| rng.pick(&["a", "b", "v"]))
+            .collect();
+        let value: String = (0..1 + rng.below(6))
+            .map(|_| rng.pick(&["x", "y", "é", "-", "=", "0"]))
+            .collect();
+
+        let attached = vec![format!("-{}o{}", flags, value)];
+        let detached = vec![format!("-{}o", flags), value.clone()];
+        for argv in [attached, detached] {
+            let m = parser().parse_from(argv.clone()).unwrap();
+            // `-o=value` drops the `=`, like `--out=value`
+            let expected = match argv.len() {
+                1 => value.strip_prefix('=').unwrap_or(&value),
+                _ => &value,
+            };
+            assert_eq!(m.get_string("out"), Some(expected), "{:?}", argv);
+            assert_eq!(m.get_bool("all"), flags.contains('a'), "{:?}", argv);
+            assert_eq!(m.get_bool("brief"), flags.contains('b'), "{:?}", argv);
+            let count = flags.matches('v').count() as i64;
+            assert_eq!(m.get_count_opt("verbose").unwrap_or(0), count, "{:?}", argv);
+        }
+    }
+}
+
+#[test]
+fn negated_bool_in_cluster_is_false() {
+    let m = parser().parse_from(vec!["-a-bv".to_string()]).unwrap();
+    assert_eq!(m.get("all"), Some(&Value::Boolean(false)));
+    assert!(m.get_bool("brief"));
+    assert_eq!(m.get_count("verbose"), 1);
+}
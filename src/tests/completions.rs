@@ -0,0 +1,76 @@
+use crate::{ArgType, Args, Shell, arg, args};
+
+fn app() -> Args {
+    args("app")
+        .arg(arg("verbose").short('v').long("verbose").flag())
+        .arg(
+            arg("color")
+                .long("color")
+                .possible_values(&["auto", "never"]),
+        )
+        .arg(arg("file").short('f').long("file").arg_type(ArgType::Path))
+}
+
+fn script(shell: Shell) -> String {
+    let mut out = Vec::new();
+    app().generate_completion(shell, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn bash_completes_values_by_type() {
+    let bash = script(Shell::Bash);
+    assert!(bash.contains("--verbose=)\n            COMPREPLY=($(compgen -W \"true false\""));
+    assert!(bash.contains("--color|--color=)\n            COMPREPLY=($(compgen -W \"auto never\""));
+    assert!(bash.contains("--file|--file=|-f)\n            COMPREPLY=($(compgen -f"));
+    assert!(!bash.contains("--help="));
+}
+
+#[test]
+fn zsh_completes_values_by_type() {
+    let zsh = script(Shell::Zsh);
+    assert!(zsh.contains("'(-v --verbose)--verbose=-:verbose:(true false)'"));
+    assert!(zsh.contains("'--color:color:(auto never)'"));
+    assert!(zsh.contains("{-f,--file}':file:_files'"));
+}
+
+#[test]
+fn fish_completes_values_by_type() {
+    let fish = script(Shell::Fish);
+    assert!(fish.contains("-f -a '--verbose=true --verbose=false'"));
+    assert!(fish.contains("-l color -r -f -a 'auto never'"));
+    assert!(fish.contains("-l file -r -F"));
+}
+
+fn full_app() -> Args {
+    app()
+        .arg(arg("dry-run").long("dry-run").flag())
+        .subcommand(args("build"))
+        .subcommand(args("clean"))
+}
+
+#[test]
+fn every_shell_lists_program_flags_and_subcommands() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let mut out = Vec::new();
+        full_app().generate_completion(shell, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("app"), "{:?}", shell);
+        for long in ["verbose", "color", "file", "dry-run", "help"] {
+            assert!(text.contains(long), "{:?} is missing {}", shell, long);
+        }
+        for sub in ["build", "clean"] {
+            assert!(text.contains(sub), "{:?} is missing {}", shell, sub);
+        }
+    }
+}
+
+#[test]
+fn possible_values_are_offered() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let mut out = Vec::new();
+        app().generate_completion(shell, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("auto never"), "{:?}", shell);
+    }
+}
@@ -0,0 +1,492 @@
+use super::{parse, temp_file, temp_path};
+use crate::{ArgType, Error, arg, args};
+
+#[test]
+fn overridden_lists_cli_values() {
+    let path = temp_file("overridden-cli.toml", "port = 1\n");
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("port").long("port").arg_type(ArgType::Integer));
+    let m = parse(parser, &["-c", path.to_str().unwrap(), "--port", "2"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(2));
+    assert_eq!(m.overridden(), ["port"]);
+}
+
+#[test]
+fn overridden_lists_env_values_beaten_by_cli() {
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var("STOML_ARGS_TEST_OVERRIDDEN_PORT", "2") };
+    let parser = args("t").arg(
+        arg("port")
+            .long("port")
+            .arg_type(ArgType::Integer)
+            .env("STOML_ARGS_TEST_OVERRIDDEN_PORT"),
+    );
+    let m = parse(parser, &["--port", "3"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(3));
+    assert_eq!(m.overridden(), ["port"]);
+}
+
+#[test]
+fn overridden_is_sorted() {
+    let toml = "zeta = 1\nalpha = 2\n";
+    let path = temp_file("overridden-sorted.toml", toml);
+    let parser = || {
+        args("t")
+            .config_arg()
+            .arg(arg("zeta").long("zeta"))
+            .arg(arg("alpha").long("alpha"))
+    };
+    let cli = ["--zeta", "3", "--alpha", "4"];
+
+    let argv = [&["-c", path.to_str().unwrap()][..], &cli].concat();
+    let m = parse(parser(), &argv).unwrap();
+    assert_eq!(m.overridden(), ["alpha", "zeta"]);
+
+    // Merging a table afterwards walks it in its own order
+    let table = stoml::parse(toml).unwrap();
+    let m = parse(parser(), &cli).unwrap().with_toml(&table);
+    assert_eq!(m.overridden(), ["alpha", "zeta"]);
+}
+
+#[test]
+fn with_toml_files_deep_merges_in_order() {
+    let base = temp_file(
+        "files-base.toml",
+        "name = \"base\"\n[server]\nhost = \"localhost\"\nport = 80\n",
+    );
+    let prod = temp_file("files-prod.toml", "[server]\nport = 443\n");
+    let m = parse(args("t"), &[])
+        .unwrap()
+        .with_toml_files(&[&base, &prod])
+        .unwrap();
+
+    assert_eq!(m.get_string("name"), Some("base"));
+    assert_eq!(m.get_integer("server.port"), Some(443));
+    assert_eq!(m.get_string("server.host"), Some("localhost"));
+    let server = m.get_map("server").unwrap();
+    assert_eq!(server.len(), 2);
+    assert_eq!(server.get("port").and_then(|v| v.as_integer()), Some(443));
+}
+
+#[test]
+fn with_toml_files_keeps_cli_values() {
+    let base = temp_file("files-cli-base.toml", "port = 1\n");
+    let prod = temp_file("files-cli-prod.toml", "port = 2\n");
+    let parser = args("t").arg(arg("port").long("port").arg_type(ArgType::Integer));
+    let m = parse(parser, &["--port", "3"])
+        .unwrap()
+        .with_toml_files(&[base, prod])
+        .unwrap();
+    assert_eq!(m.get_integer("port"), Some(3));
+}
+
+const TEMPLATE: &str = "# generated\nport = 7000\n";
+
+fn port_arg() -> crate::Arg {
+    arg("port").long("port").arg_type(ArgType::Integer)
+}
+
+#[test]
+fn template_creates_missing_default_config() {
+    let path = temp_path("template-default.toml");
+    let parser = args("t")
+        .config_arg_default(path.to_str().unwrap())
+        .config_template(TEMPLATE)
+        .arg(port_arg());
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), TEMPLATE);
+    assert_eq!(m.get_integer("port"), Some(7000));
+    assert_eq!(m.config_path(), path.to_str());
+}
+
+#[test]
+fn template_never_writes_explicit_path() {
+    let default = temp_path("template-unused-default.toml");
+    let explicit = temp_path("template-explicit.toml");
+    let parser = args("t")
+        .config_arg_default(default.to_str().unwrap())
+        .config_template(TEMPLATE)
+        .arg(port_arg());
+    let m = parse(parser, &["-c", explicit.to_str().unwrap()]).unwrap();
+    assert!(!explicit.exists());
+    assert!(!default.exists());
+    assert_eq!(m.get_integer("port"), None);
+}
+
+#[test]
+fn template_write_failure_is_io_error() {
+    let dir = temp_path("template-missing-dir");
+    let path = dir.join("config.toml");
+    let parser = args("t")
+        .config_arg_default(path.to_str().unwrap())
+        .config_template(TEMPLATE);
+    let err = parse(parser, &[]).unwrap_err();
+    assert!(matches!(err, Error::Io(_)), "{:?}", err);
+}
+
+#[test]
+fn required_config_missing_is_an_error() {
+    let path = temp_path("required-missing.toml");
+    let parser = || {
+        args("t")
+            .config_arg_default(path.to_str().unwrap())
+            .config_required(true)
+    };
+    let err = parse(parser(), &[]).unwrap_err();
+    assert!(matches!(&err, Error::MissingConfig { path: p } if *p == path.to_str().unwrap()));
+
+    let explicit = temp_path("required-missing-explicit.toml");
+    let err = parse(parser(), &["-c", explicit.to_str().unwrap()]).unwrap_err();
+    assert!(matches!(err, Error::MissingConfig { .. }));
+}
+
+#[test]
+fn required_config_present_loads() {
+    let path = temp_file("required-present.toml", "port = 5\n");
+    let parser = args("t")
+        .config_arg_default(path.to_str().unwrap())
+        .config_required(true)
+        .arg(port_arg());
+    assert_eq!(parse(parser, &[]).unwrap().get_integer("port"), Some(5));
+}
+
+#[test]
+fn required_config_is_satisfied_by_template() {
+    let path = temp_path("required-template.toml");
+    let parser = args("t")
+        .config_arg_default(path.to_str().unwrap())
+        .config_required(true)
+        .config_template(TEMPLATE)
+        .arg(port_arg());
+    assert_eq!(parse(parser, &[]).unwrap().get_integer("port"), Some(7000));
+    assert!(path.exists());
+}
+
+#[test]
+fn optional_config_missing_is_silent() {
+    let path = temp_path("optional-missing.toml");
+    let parser = args("t")
+        .config_arg_default(path.to_str().unwrap())
+        .config_required(false);
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(m.config_path(), None);
+}
+
+#[test]
+fn include_cycle_is_reported() {
+    let a = temp_path("cycle-a.toml");
+    let b = temp_path("cycle-b.toml");
+    let name = |p: &std::path::Path| p.file_name().unwrap().to_str().unwrap().to_string();
+    std::fs::write(&a, format!("include = [\"{}\"]\nx = 1\n", name(&b))).unwrap();
+    std::fs::write(&b, format!("include = [\"{}\"]\ny = 2\n", name(&a))).unwrap();
+
+    let err = parse(args("t").config_arg(), &["-c", a.to_str().unwrap()]).unwrap_err();
+    assert!(matches!(err, Error::ConfigCycle { .. }), "{:?}", err);
+}
+
+#[test]
+fn self_include_is_reported() {
+    let a = temp_path("cycle-self.toml");
+    let name = a.file_name().unwrap().to_str().unwrap();
+    std::fs::write(&a, format!("include = [\"{}\"]\n", name)).unwrap();
+    let err = parse(args("t").config_arg(), &["-c", a.to_str().unwrap()]).unwrap_err();
+    assert!(matches!(err, Error::ConfigCycle { .. }), "{:?}", err);
+}
+
+#[test]
+fn include_must_be_an_array_of_paths() {
+    let base = temp_file("include-shape-base.toml", "x = 1\n");
+    let base = base.file_name().unwrap().to_str().unwrap();
+    for (name, include) in [
+        ("include-shape-string.toml", format!("\"{}\"", base)),
+        ("include-shape-number.toml", "1".to_string()),
+        ("include-shape-mixed.toml", format!("[\"{}\", 1]", base)),
+    ] {
+        let path = temp_file(name, &format!("include = {}\n", include));
+        let err = parse(args("t").config_arg(), &["-c", path.to_str().unwrap()]).unwrap_err();
+        assert!(matches!(err, Error::InvalidInclude { .. }), "{:?}", err);
+    }
+}
+
+#[test]
+fn with_toml_file_resolves_includes() {
+    let base = temp_file("include-file-base.toml", "x = 1\ny = 1\n");
+    let top = temp_file(
+        "include-file-top.toml",
+        &format!(
+            "include = [\"{}\"]\ny = 2\n",
+            base.file_name().unwrap().to_str().unwrap()
+        ),
+    );
+    let m = parse(args("t"), &[]).unwrap().with_toml_file(&top).unwrap();
+    assert_eq!(m.get_integer("x"), Some(1));
+    assert_eq!(m.get_integer("y"), Some(2));
+    assert!(m.get("include").is_none());
+
+    let m = parse(args("t"), &[])
+        .unwrap()
+        .with_toml_files(&[&top])
+        .unwrap();
+    assert_eq!(m.get_integer("x"), Some(1));
+}
+
+#[test]
+fn diamond_includes_are_not_a_cycle() {
+    let base = temp_file("diamond-base.toml", "x = 1\n");
+    let base = base.file_name().unwrap().to_str().unwrap();
+    let left = temp_file("diamond-left.toml", &format!("include = [\"{}\"]\n", base));
+    let right = temp_file("diamond-right.toml", &format!("include = [\"{}\"]\n", base));
+    let top = temp_file(
+        "diamond-top.toml",
+        &format!(
+            "include = [\"{}\", \"{}\"]\n",
+            left.file_name().unwrap().to_str().unwrap(),
+            right.file_name().unwrap().to_str().unwrap()
+        ),
+    );
+    let m = parse(args("t").config_arg(), &["-c", top.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_integer("x"), Some(1));
+}
+
+#[test]
+fn to_toml_string_round_trips() {
+    let path = temp_file(
+        "round-trip.toml",
+        "name = \"svc\"\n[server]\nhost = \"h\"\nports = [80, 443]\n",
+    );
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("name").long("name"))
+        .arg(arg("host").long("host").toml_key("server.host"))
+        .arg(
+            arg("ports")
+                .long("port")
+                .arg_type(ArgType::Array)
+                .toml_key("server.ports"),
+        )
+        .arg(arg("debug").long("debug").flag())
+        .arg(
+            arg("ratio")
+                .long("ratio")
+                .arg_type(ArgType::Float)
+                .toml_key("tuning.ratio"),
+        );
+    let m = parse(
+        parser,
+        &["-c", path.to_str().unwrap(), "--debug", "--ratio", "0.5"],
+    )
+    .unwrap();
+
+    let toml = m.to_toml_string().unwrap();
+    assert!(toml.contains("[server]"), "{}", toml);
+    assert!(toml.contains("[tuning]"), "{}", toml);
+    assert_eq!(stoml::parse(&toml).unwrap(), m.to_table());
+}
+
+#[test]
+fn to_toml_string_quotes_awkward_strings_and_keys() {
+    let parser = args("t")
+        .arg(arg("msg").long("msg"))
+        .arg(arg("odd").long("odd").toml_key("odd key"));
+    let m = parse(parser, &["--msg", "a \"quoted\"\nline\\", "--odd", "x"]).unwrap();
+    let toml = m.to_toml_string().unwrap();
+    assert_eq!(stoml::parse(&toml).unwrap(), m.to_table());
+}
+
+#[test]
+fn bool_args_accept_truthy_strings_from_config() {
+    let path = temp_file(
+        "bool-strings.toml",
+        "tls = \"yes\"\ncolor = \"OFF\"\ndebug = \"1\"\n",
+    );
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("tls").long("tls").flag())
+        .arg(arg("color").long("color").flag())
+        .arg(arg("debug").long("debug").flag());
+    let m = parse(parser, &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_bool_opt("tls"), Some(true));
+    assert_eq!(m.get_bool_opt("color"), Some(false));
+    assert_eq!(m.get_bool_opt("debug"), Some(true));
+}
+
+#[test]
+fn bool_args_warn_about_other_strings_from_config() {
+    let path = temp_file("bool-garbage.toml", "tls = \"maybe\"\n");
+    let parser = args("t").config_arg().arg(arg("tls").long("tls").flag());
+    let m = parse(parser, &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_bool_opt("tls"), None);
+    assert!(matches!(
+        m.warnings(),
+        [crate::Warning::TypeMismatch { arg, .. }] if arg == "tls"
+    ));
+}
+
+fn lookup_args() -> crate::Args {
+    args("t")
+        .config_arg()
+        .arg(
+            arg("color")
+                .long("color")
+                .alias("colour")
+                .toml_key("ui.color"),
+        )
+        .arg(
+            arg("port")
+                .long("port")
+                .arg_type(ArgType::Integer)
+                .toml_key("server.port"),
+        )
+}
+
+#[test]
+fn get_resolves_names_keys_and_aliases_from_cli() {
+    let m = parse(lookup_args(), &["--colour", "red", "--port", "80"]).unwrap();
+    for key in ["color", "ui.color", "colour"] {
+        assert_eq!(m.get_string(key), Some("red"), "{}", key);
+    }
+    assert_eq!(m.get_integer("port"), Some(80));
+    assert_eq!(m.get_integer("server.port"), Some(80));
+}
+
+#[test]
+fn get_resolves_names_keys_and_aliases_from_config() {
+    let path = temp_file(
+        "lookup.toml",
+        "[ui]\ncolor = \"blue\"\n[server]\nport = 8080\n",
+    );
+    let m = parse(lookup_args(), &["-c", path.to_str().unwrap()]).unwrap();
+    for key in ["color", "ui.color", "colour"] {
+        assert_eq!(m.get_string(key), Some("blue"), "{}", key);
+    }
+    assert_eq!(m.get_integer("port"), Some(8080));
+    assert_eq!(m.get_integer("server.port"), Some(8080));
+}
+
+#[test]
+fn config_env_loads_the_named_file() {
+    let env_file = temp_file("config-env.toml", "port = 1\n");
+    let default_file = temp_file("config-env-default.toml", "port = 2\n");
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var("STOML_ARGS_TEST_CONFIG_ENV", &env_file) };
+    let parser = args("t")
+        .config_arg_default(default_file.to_str().unwrap())
+        .config_env("STOML_ARGS_TEST_CONFIG_ENV")
+        .arg(port_arg());
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(1));
+    assert_eq!(m.config_path(), env_file.to_str());
+}
+
+#[test]
+fn config_flag_beats_config_env() {
+    let env_file = temp_file("config-env-loses.toml", "port = 1\n");
+    let flag_file = temp_file("config-env-flag.toml", "port = 3\n");
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var("STOML_ARGS_TEST_CONFIG_ENV_FLAG", &env_file) };
+    let parser = || {
+        args("t")
+            .config_env("STOML_ARGS_TEST_CONFIG_ENV_FLAG")
+            .arg(port_arg())
+    };
+    let m = parse(parser(), &["-c", flag_file.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(3));
+    let flag = format!("--config={}", flag_file.display());
+    let m = parse(parser(), &[&flag]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(3));
+}
+
+#[test]
+fn first_existing_search_path_wins() {
+    let missing = temp_path("search-missing.toml");
+    let first = temp_file("search-first.toml", "port = 10\n");
+    let second = temp_file("search-second.toml", "port = 20\nname = \"x\"\n");
+    let paths = [&missing, &first, &second]
+        .iter()
+        .map(|p| p.to_str().unwrap().to_string())
+        .collect();
+    let parser = args("t")
+        .config_search_paths(paths)
+        .arg(port_arg())
+        .arg(arg("name").long("name"));
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(10));
+    assert_eq!(m.get_string("name"), None);
+    assert_eq!(m.config_path(), first.to_str());
+}
+
+#[test]
+fn no_existing_search_path_loads_nothing() {
+    let paths = vec![
+        temp_path("search-none-a.toml")
+            .to_str()
+            .unwrap()
+            .to_string(),
+        temp_path("search-none-b.toml")
+            .to_str()
+            .unwrap()
+            .to_string(),
+    ];
+    let parser = args("t").config_search_paths(paths).arg(port_arg());
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(m.get_integer("port"), None);
+    assert_eq!(m.config_path(), None);
+}
+
+fn defaults_then_config(override_: bool) -> crate::Matches {
+    let defs = [port_arg().default(80), arg("name").long("name")];
+    let config = stoml::parse("port = 8080\nname = \"cfg\"\n").unwrap();
+    let parser = defs.iter().cloned().fold(args("t"), crate::Args::arg);
+    let m = parse(parser, &["--name", "cli"])
+        .unwrap()
+        .with_defaults(&defs);
+    if override_ {
+        m.with_toml_override(&config)
+    } else {
+        m.with_toml(&config)
+    }
+}
+
+#[test]
+fn with_toml_fills_only_missing_values() {
+    let m = defaults_then_config(false);
+    assert_eq!(m.get_integer("port"), Some(80));
+    assert_eq!(m.get_string("name"), Some("cli"));
+}
+
+#[test]
+fn with_toml_override_replaces_values() {
+    let m = defaults_then_config(true);
+    assert_eq!(m.get_integer("port"), Some(8080));
+    assert_eq!(m.get_string("name"), Some("cfg"));
+}
+
+#[test]
+fn override_toml_keeps_unrelated_values() {
+    let mut m = defaults_then_config(false);
+    m.override_toml(&stoml::parse("port = 1\n").unwrap());
+    assert_eq!(m.get_integer("port"), Some(1));
+    assert_eq!(m.get_string("name"), Some("cli"));
+}
+
+#[test]
+fn default_if_reads_config_values_by_toml_key() {
+    let path = temp_file("default-if-config.toml", "[app]\nmode = \"safe\"\n");
+    let defs = vec![
+        arg("mode").long("mode").toml_key("app.mode"),
+        arg("retries")
+            .long("retries")
+            .arg_type(ArgType::Integer)
+            .default(3i64)
+            .default_if("mode", "safe", 1i64),
+    ];
+    let parser = defs
+        .iter()
+        .cloned()
+        .fold(args("t").config_arg(), crate::Args::arg);
+    let m = parse(parser, &["-c", path.to_str().unwrap()])
+        .unwrap()
+        .with_defaults(&defs);
+    assert_eq!(m.get_integer("retries"), Some(1));
+}
@@ -0,0 +1,78 @@
+use serde::Deserialize;
+
+use super::{parse, temp_file};
+use crate::{ArgType, Args, Error, arg, args};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Config {
+    verbose: bool,
+    server: Server,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Server {
+    host: String,
+    port: i64,
+    tags: Vec<String>,
+}
+
+fn server_args() -> Args {
+    args("t")
+        .config_arg()
+        .arg(arg("verbose").long("verbose").flag())
+        .arg(arg("host").long("host").toml_key("server.host"))
+        .arg(
+            arg("port")
+                .long("port")
+                .arg_type(ArgType::Integer)
+                .toml_key("server.port"),
+        )
+        .arg(
+            arg("tags")
+                .long("tag")
+                .arg_type(ArgType::Array)
+                .toml_key("server.tags"),
+        )
+}
+
+#[test]
+fn dotted_keys_fill_nested_structs() {
+    let path = temp_file("de.toml", "[server]\nhost = \"example.org\"\n");
+    let m = parse(
+        server_args(),
+        &[
+            "-c",
+            path.to_str().unwrap(),
+            "--verbose",
+            "--port",
+            "8080",
+            "--tag",
+            "a",
+            "--tag",
+            "b",
+        ],
+    )
+    .unwrap();
+    let cfg: Config = m.deserialize().unwrap();
+    assert_eq!(
+        cfg,
+        Config {
+            verbose: true,
+            server: Server {
+                host: "example.org".into(),
+                port: 8080,
+                tags: vec!["a".into(), "b".into()],
+            },
+        }
+    );
+}
+
+#[test]
+fn missing_fields_are_deserialize_errors() {
+    let m = parse(server_args(), &["--verbose", "--host", "h"]).unwrap();
+    let err = m.deserialize::<Config>().unwrap_err();
+    let Error::Deserialize { message } = err else {
+        panic!("expected Deserialize, got {:?}", err);
+    };
+    assert!(message.contains("port"), "{}", message);
+}
@@ -0,0 +1,89 @@
+use super::parse;
+use crate::{Error, arg, args};
+
+#[test]
+fn untypeable_short_flags_are_rejected() {
+    for c in [' ', '\t', '\n', '\u{7}', '-', '='] {
+        let err = parse(args("t").arg(arg("x").short(c)), &[]).unwrap_err();
+        assert!(
+            matches!(&err, Error::InvalidDefinition { reason } if reason.contains("short flag")),
+            "{:?}: {:?}",
+            c,
+            err
+        );
+    }
+}
+
+#[test]
+fn untypeable_long_flags_are_rejected() {
+    for long in ["", "out=file", "dry run", "tab\tbed"] {
+        let err = parse(args("t").arg(arg("x").long(long)), &[]).unwrap_err();
+        assert!(
+            matches!(&err, Error::InvalidDefinition { reason } if reason.contains("long flag")),
+            "{:?}: {:?}",
+            long,
+            err
+        );
+    }
+    let err = parse(args("t").arg(arg("x").long("x").alias("a b")), &[]).unwrap_err();
+    assert!(matches!(err, Error::InvalidDefinition { .. }));
+}
+
+#[test]
+fn printable_flags_are_accepted() {
+    let parser = args("t")
+        .arg(arg("x").short('x').long("dry-run"))
+        .arg(arg("u").short('ü').long("über"));
+    assert!(parser.validate().is_ok());
+    let m = parse(parser, &["-ü", "1", "--dry-run", "2"]).unwrap();
+    assert_eq!(m.get_string("u"), Some("1"));
+}
+
+#[test]
+fn validate_reports_flag_problems() {
+    let parser = args("t").arg(arg("x").short(' ')).arg(arg("y").long("a=b"));
+    let Err(Error::InvalidDefinitions { reasons }) = parser.validate() else {
+        panic!("expected InvalidDefinitions");
+    };
+    assert_eq!(reasons.len(), 2);
+}
+
+#[test]
+fn implication_cycle_is_a_definition_problem() {
+    let parser = args("t")
+        .arg(arg("a").long("a").flag().implies("b", true))
+        .arg(arg("b").long("b").flag().implies("c", true))
+        .arg(arg("c").long("c").flag().implies("a", true));
+    let Err(Error::InvalidDefinitions { reasons }) = parser.validate() else {
+        panic!("expected InvalidDefinitions");
+    };
+    assert!(
+        reasons.iter().any(|r| r.starts_with("implication cycle: ")),
+        "{:?}",
+        reasons
+    );
+}
+
+#[test]
+fn conditional_default_cycle_is_a_definition_problem() {
+    let parser = args("t")
+        .arg(arg("a").long("a").default_if("b", "x", "y"))
+        .arg(arg("b").long("b").default_if("a", "x", "y"));
+    let Err(Error::InvalidDefinitions { reasons }) = parser.validate() else {
+        panic!("expected InvalidDefinitions");
+    };
+    assert!(
+        reasons
+            .iter()
+            .any(|r| r.starts_with("conditional default cycle: "))
+    );
+}
+
+#[test]
+fn implication_chain_is_fine() {
+    let parser = args("t")
+        .arg(arg("a").long("a").flag().implies("b", true))
+        .arg(arg("b").long("b").flag().implies("c", true))
+        .arg(arg("c").long("c").flag());
+    assert!(parser.validate().is_ok());
+}
@@ -0,0 +1,133 @@
+//! Each test uses its own variable names, since tests run in parallel
+
+use super::parse;
+use crate::{ArgType, arg, args};
+
+fn set(var: &str, value: &str) {
+    // SAFETY: every test uses distinct variable names
+    unsafe { std::env::set_var(var, value) };
+}
+
+fn tags(var: &str) -> crate::Args {
+    args("t").arg(arg("tags").long("tag").arg_type(ArgType::Array).env(var))
+}
+
+#[test]
+fn array_env_splits_on_commas() {
+    set("STOML_ARGS_TEST_SPLIT", "a,b,c");
+    let m = parse(tags("STOML_ARGS_TEST_SPLIT"), &[]).unwrap();
+    assert_eq!(m.get_string_vec("tags"), ["a", "b", "c"]);
+}
+
+#[test]
+fn array_env_reads_indexed_vars() {
+    set("STOML_ARGS_TEST_INDEXED_0", "a,b");
+    set("STOML_ARGS_TEST_INDEXED_1", "c");
+    let m = parse(tags("STOML_ARGS_TEST_INDEXED"), &[]).unwrap();
+    assert_eq!(m.get_string_vec("tags"), ["a,b", "c"]);
+}
+
+#[test]
+fn indexed_vars_stop_at_first_gap() {
+    set("STOML_ARGS_TEST_GAP_0", "a");
+    set("STOML_ARGS_TEST_GAP_2", "c");
+    let m = parse(tags("STOML_ARGS_TEST_GAP"), &[]).unwrap();
+    assert_eq!(m.get_string_vec("tags"), ["a"]);
+}
+
+#[test]
+fn plain_var_wins_over_indexed_vars() {
+    set("STOML_ARGS_TEST_BOTH", "x,y");
+    set("STOML_ARGS_TEST_BOTH_0", "a");
+    let m = parse(tags("STOML_ARGS_TEST_BOTH"), &[]).unwrap();
+    assert_eq!(m.get_string_vec("tags"), ["x", "y"]);
+}
+
+#[test]
+fn cli_wins_over_indexed_vars() {
+    set("STOML_ARGS_TEST_CLI_0", "a");
+    let m = parse(tags("STOML_ARGS_TEST_CLI"), &["--tag", "z"]).unwrap();
+    assert_eq!(m.get_string_vec("tags"), ["z"]);
+}
+
+#[test]
+fn resolve_reads_indexed_vars() {
+    set("STOML_ARGS_TEST_RESOLVE_0", "a");
+    set("STOML_ARGS_TEST_RESOLVE_1", "b");
+    let defs = [arg("tags")
+        .arg_type(ArgType::Array)
+        .env("STOML_ARGS_TEST_RESOLVE")];
+    let m = parse(args("t"), &[]).unwrap().resolve(&defs, None);
+    assert_eq!(m.get_string_vec("tags"), ["a", "b"]);
+}
+
+fn port(var: &str) -> crate::Args {
+    args("t").config_arg().arg(
+        arg("port")
+            .long("port")
+            .arg_type(ArgType::Integer)
+            .default(80)
+            .env(var),
+    )
+}
+
+#[test]
+fn env_fills_a_missing_value() {
+    set("STOML_ARGS_TEST_PORT_FILL", "8080");
+    let m = parse(port("STOML_ARGS_TEST_PORT_FILL"), &[]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(8080));
+    assert_eq!(m.source("port"), Some(crate::ValueSource::Env));
+}
+
+#[test]
+fn cli_wins_over_env() {
+    set("STOML_ARGS_TEST_PORT_CLI", "8080");
+    let m = parse(port("STOML_ARGS_TEST_PORT_CLI"), &["--port", "9000"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(9000));
+}
+
+#[test]
+fn env_wins_over_config() {
+    set("STOML_ARGS_TEST_PORT_TOML", "8080");
+    let path = super::temp_file("env-over-toml.toml", "port = 7000\n");
+    let m = parse(
+        port("STOML_ARGS_TEST_PORT_TOML"),
+        &["-c", path.to_str().unwrap()],
+    )
+    .unwrap();
+    assert_eq!(m.get_integer("port"), Some(8080));
+}
+
+#[test]
+fn env_values_are_typed() {
+    set("STOML_ARGS_TEST_PORT_BAD", "eighty");
+    let err = parse(port("STOML_ARGS_TEST_PORT_BAD"), &[]).unwrap_err();
+    assert!(
+        matches!(err, crate::Error::InvalidValue { .. }),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn bool_env_accepts_truthy_strings() {
+    let flag = |var| args("t").arg(arg("debug").long("debug").flag().env(var));
+    set("STOML_ARGS_TEST_BOOL_YES", "yes");
+    set("STOML_ARGS_TEST_BOOL_ONE", "1");
+    set("STOML_ARGS_TEST_BOOL_OFF", "off");
+    assert!(
+        parse(flag("STOML_ARGS_TEST_BOOL_YES"), &[])
+            .unwrap()
+            .get_bool("debug")
+    );
+    assert!(
+        parse(flag("STOML_ARGS_TEST_BOOL_ONE"), &[])
+            .unwrap()
+            .get_bool("debug")
+    );
+    assert!(
+        !parse(flag("STOML_ARGS_TEST_BOOL_OFF"), &[])
+            .unwrap()
+            .get_bool("debug")
+    );
+}
@@ -0,0 +1,158 @@
+use super::parse;
+use crate::{Error, arg, args, exit_status};
+
+#[test]
+fn exit_code_follows_sysexits() {
+    let parser = || {
+        args("t")
+            .version("1")
+            .arg(arg("port").long("port").arg_type(crate::ArgType::Integer))
+    };
+    let usage = [
+        parse(parser(), &["--nope"]).unwrap_err(),
+        parse(parser(), &["--port"]).unwrap_err(),
+        parse(parser(), &["--port", "x"]).unwrap_err(),
+        parse(parser(), &["extra"]).unwrap_err(),
+        Error::MissingRequired {
+            name: "port".into(),
+        },
+    ];
+    for e in &usage {
+        assert_eq!(e.exit_code(), 64, "{:?}", e);
+    }
+
+    let io = Error::Io(std::io::Error::other("disk"));
+    assert_eq!(io.exit_code(), 74);
+    assert_eq!(
+        Error::Deserialize {
+            message: "x".into()
+        }
+        .exit_code(),
+        1
+    );
+
+    assert_eq!(parse(parser(), &["--help"]).unwrap_err().exit_code(), 0);
+    assert_eq!(parse(parser(), &["--version"]).unwrap_err().exit_code(), 0);
+}
+
+#[test]
+fn error_exit_code_overrides_errors_only() {
+    let unknown = Error::UnknownFlag {
+        flag: "--nope".into(),
+        position: None,
+    };
+    assert_eq!(exit_status(&unknown, None), 64);
+    assert_eq!(exit_status(&unknown, Some(2)), 2);
+    assert_eq!(exit_status(&Error::Help("usage".into()), Some(2)), 0);
+    assert_eq!(exit_status(&Error::Version("t 1".into()), Some(2)), 0);
+}
+
+#[test]
+fn close_typo_suggests_a_flag() {
+    let parser = args("t").arg(arg("verbose").long("verbose").flag());
+    let err = parse(parser, &["--verbsoe"]).unwrap_err();
+    let Error::UnknownFlagSuggest {
+        ref flag,
+        ref suggestion,
+        ..
+    } = err
+    else {
+        panic!("expected UnknownFlagSuggest, got {:?}", err);
+    };
+    assert_eq!(
+        (flag.as_str(), suggestion.as_str()),
+        ("--verbsoe", "--verbose")
+    );
+    assert!(
+        err.to_string()
+            .contains("unknown flag '--verbsoe' (did you mean '--verbose'?)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn distant_typo_is_a_plain_unknown_flag() {
+    let parser = args("t").arg(arg("verbose").long("verbose").flag());
+    let err = parse(parser, &["--frobnicate"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { .. }), "{:?}", err);
+}
+
+fn typo_error() -> Error {
+    let parser = args("t").arg(arg("verbose").long("verbose").flag());
+    parse(parser, &["--verbsoe"]).unwrap_err()
+}
+
+#[test]
+fn colored_errors_highlight_prefix_and_suggestion() {
+    let line = typo_error().render(true);
+    assert!(line.starts_with("\x1b[1;31merror:\x1b[0m "), "{:?}", line);
+    assert!(line.contains("\x1b[32m--verbose\x1b[0m"), "{:?}", line);
+}
+
+#[test]
+fn colored_errors_highlight_subcommand_suggestion() {
+    let parser = args("t").subcommand(args("build"));
+    let line = parse(parser, &["biuld"]).unwrap_err().render(true);
+    assert!(line.contains("\x1b[32mbuild\x1b[0m"), "{:?}", line);
+}
+
+#[test]
+fn plain_errors_have_no_color_codes() {
+    let line = typo_error().render(false);
+    assert_eq!(
+        line,
+        "error: unknown flag '--verbsoe' (did you mean '--verbose'?)"
+    );
+}
+
+#[test]
+fn no_color_disables_colors() {
+    // SAFETY: no other test reads or writes NO_COLOR
+    unsafe { std::env::set_var("NO_COLOR", "1") };
+    assert!(!crate::error::stderr_color());
+    assert!(
+        !typo_error()
+            .render(crate::error::stderr_color())
+            .contains('\x1b')
+    );
+}
+
+fn argv(tokens: &[&str]) -> Vec<String> {
+    tokens.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn annotated_marks_an_invalid_value() {
+    let parser = args("t")
+        .arg(arg("verbose").long("verbose").flag())
+        .arg(arg("port").long("port").arg_type(crate::ArgType::Integer));
+    let argv = argv(&["--verbose", "--port", "abc"]);
+    let err = parser.parse_from(argv.clone()).unwrap_err();
+    assert_eq!(
+        err.annotated(&argv),
+        "error: invalid value 'abc' for 'port': expected an integer\n  \
+         --verbose --port abc\n                   ^^^"
+    );
+}
+
+#[test]
+fn annotated_marks_an_unknown_flag() {
+    let parser = args("t").arg(arg("verbose").long("verbose").flag());
+    let argv = argv(&["--verbose", "--bogus", "x"]);
+    let err = parser.parse_from(argv.clone()).unwrap_err();
+    assert!(
+        err.annotated(&argv)
+            .ends_with("\n  --verbose --bogus x\n            ^^^^^^^"),
+        "{}",
+        err.annotated(&argv)
+    );
+}
+
+#[test]
+fn annotated_without_position_is_one_line() {
+    let parser = args("t").arg(arg("name").long("name").required());
+    let argv = argv(&[]);
+    let err = parser.parse_from(argv.clone()).unwrap_err();
+    assert_eq!(err.annotated(&argv), format!("error: {}", err));
+}
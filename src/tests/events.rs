@@ -0,0 +1,53 @@
+use super::{parse, temp_file};
+use crate::{Args, ParseEvent, arg, args};
+use std::sync::{Arc, Mutex};
+
+/// Attach an observer that records every event
+fn observed(parser: Args) -> (Args, Arc<Mutex<Vec<ParseEvent>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let parser = parser.on_parse_event(move |e| sink.lock().unwrap().push(e));
+    (parser, events)
+}
+
+#[test]
+fn events_follow_resolution_order() {
+    let path = temp_file("events.toml", "level = \"debug\"\n");
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var("STOML_ARGS_TEST_EVENTS_HOST", "example.org") };
+    let defs = [
+        arg("host").long("host").env("STOML_ARGS_TEST_EVENTS_HOST"),
+        arg("level").long("level"),
+        arg("port").long("port").default(8080i64),
+    ];
+    let (parser, events) = observed(defs.iter().cloned().fold(args("t").config_arg(), Args::arg));
+
+    let m = parse(parser, &["-c", path.to_str().unwrap()])
+        .unwrap()
+        .with_defaults(&defs);
+    assert_eq!(m.get_integer("port"), Some(8080));
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            ParseEvent::ConfigLoaded {
+                path: path.to_str().unwrap().to_string()
+            },
+            ParseEvent::EnvResolved {
+                var: "STOML_ARGS_TEST_EVENTS_HOST".to_string()
+            },
+            ParseEvent::DefaultApplied {
+                name: "port".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn no_events_without_absent_values() {
+    let defs = [arg("port").long("port").default(8080i64)];
+    let (parser, events) = observed(args("t").arg(defs[0].clone()));
+    parse(parser, &["--port", "1"])
+        .unwrap()
+        .with_defaults(&defs);
+    assert!(events.lock().unwrap().is_empty());
+}
@@ -0,0 +1,400 @@
+use super::parse;
+use crate::{Args, Error, arg, args};
+
+fn cache_args() -> Args {
+    args("t")
+        .infer_long_args(true)
+        .arg(arg("no-cache").long("no-cache").flag())
+        .arg(arg("cache-dir").long("cache-dir"))
+        .arg(arg("cache-size").long("cache-size"))
+}
+
+#[test]
+fn exact_long_beats_prefix() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("verb").long("verb").flag())
+        .arg(arg("verbose").long("verbose").flag());
+    let m = parse(parser, &["--verb"]).unwrap();
+    assert!(m.get_bool("verb"));
+    assert!(!m.get_bool("verbose"));
+}
+
+#[test]
+fn unique_prefix_resolves() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("verbose").long("verbose").flag());
+    assert!(parse(parser, &["--verb"]).unwrap().get_bool("verbose"));
+}
+
+#[test]
+fn ambiguous_prefix_fails() {
+    let err = parse(cache_args(), &["--cache", "x"]).unwrap_err();
+    assert!(matches!(err, Error::AmbiguousFlag { candidates, .. } if candidates.len() == 2));
+}
+
+#[test]
+fn exact_no_flag_beats_negation() {
+    let m = parse(cache_args(), &["--no-cache"]).unwrap();
+    assert!(m.get_bool("no-cache"));
+}
+
+#[test]
+fn exact_no_flag_beats_negated_prefix() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("no-cache").long("no-cache").flag())
+        .arg(
+            arg("cache-enabled")
+                .long("cache-enabled")
+                .flag()
+                .default(true),
+        );
+    let m = parse(parser, &["--no-cache"]).unwrap();
+    assert!(m.get_bool("no-cache"));
+    assert_eq!(m.get("cache-enabled"), None);
+}
+
+#[test]
+fn negation_uses_exact_name_before_prefix() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("verbose").long("verbose").flag())
+        .arg(arg("no-verbose-log").long("no-verbose-log").flag());
+    let m = parse(parser, &["--verbose", "--no-verbose"]).unwrap();
+    assert!(!m.get_bool("verbose"));
+    assert!(!m.get_bool("no-verbose-log"));
+}
+
+#[test]
+fn negation_of_prefix() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("verbose").long("verbose").flag());
+    let m = parse(parser, &["--verbose", "--no-verb"]).unwrap();
+    assert!(!m.get_bool("verbose"));
+}
+
+#[test]
+fn subcommand_flags_win_after_the_name() {
+    let parser = || {
+        args("t")
+            .infer_long_args(true)
+            .arg(arg("verbose").long("verbose").flag())
+            .subcommand(
+                args("run")
+                    .infer_long_args(true)
+                    .arg(arg("verify").long("verify").flag()),
+            )
+    };
+
+    let m = parse(parser(), &["--ver", "run"]).unwrap();
+    assert!(m.get_bool("verbose"));
+
+    let m = parse(parser(), &["run", "--ver"]).unwrap();
+    assert!(!m.get_bool("verbose"));
+    let (name, sub) = m.subcommand().unwrap();
+    assert_eq!(name, "run");
+    assert!(sub.get_bool("verify"));
+}
+
+#[test]
+fn ambiguity_is_per_scope() {
+    let parser = args("t")
+        .infer_long_args(true)
+        .arg(arg("verbose").long("verbose").flag())
+        .subcommand(
+            args("run")
+                .infer_long_args(true)
+                .arg(arg("verify").long("verify").flag())
+                .arg(arg("version-file").long("version-file")),
+        );
+    let err = parse(parser, &["run", "--ver"]).unwrap_err();
+    assert!(matches!(err, Error::AmbiguousFlag { .. }));
+}
+
+#[test]
+fn subcommand_names_are_not_abbreviated() {
+    let parser = args("t").infer_long_args(true).subcommand(args("server"));
+    let err = parse(parser, &["serv"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownSubcommand { .. }));
+}
+
+#[test]
+fn short_bool_with_trailing_dash_is_false() {
+    let parser = || {
+        args("t")
+            .arg(arg("tls").short('t').long("tls").flag())
+            .arg(arg("verbose").short('v').flag())
+    };
+    let m = parse(parser(), &["-t-"]).unwrap();
+    assert_eq!(m.get_bool_opt("tls"), Some(false));
+
+    let m = parse(parser(), &["-t-v"]).unwrap();
+    assert_eq!(m.get_bool_opt("tls"), Some(false));
+    assert!(m.get_bool("verbose"));
+
+    let m = parse(parser(), &["-vt"]).unwrap();
+    assert!(m.get_bool("tls"));
+}
+
+#[test]
+fn later_short_negation_wins() {
+    let parser = args("t").arg(arg("tls").short('t').flag());
+    let m = parse(parser, &["-t", "-t-"]).unwrap();
+    assert!(!m.get_bool("tls"));
+}
+
+#[test]
+fn trailing_dash_after_value_flag_is_the_value() {
+    let parser = args("t").arg(arg("out").short('o'));
+    let m = parse(parser, &["-o-"]).unwrap();
+    assert_eq!(m.get_string("out"), Some("-"));
+}
+
+fn ignoring_args() -> Args {
+    args("t")
+        .ignore_unknown_flags()
+        .arg(arg("verbose").short('v').flag())
+        .arg(arg("quiet").short('q').flag())
+        .arg(arg("out").short('o'))
+}
+
+#[test]
+fn unknown_shorts_in_cluster_pass_through() {
+    let m = parse(ignoring_args(), &["-vxq"]).unwrap();
+    assert!(m.get_bool("verbose"));
+    assert!(m.get_bool("quiet"));
+    assert_eq!(m.remaining(), ["-x"]);
+}
+
+#[test]
+fn each_unknown_short_passes_through_separately() {
+    let m = parse(ignoring_args(), &["-xvy", "--zap=1"]).unwrap();
+    assert!(m.get_bool("verbose"));
+    assert_eq!(m.remaining(), ["-x", "-y", "--zap=1"]);
+}
+
+#[test]
+fn known_value_flag_after_unknown_takes_rest_of_cluster() {
+    let m = parse(ignoring_args(), &["-xofile"]).unwrap();
+    assert_eq!(m.get_string("out"), Some("file"));
+    assert_eq!(m.remaining(), ["-x"]);
+}
+
+#[test]
+fn unknown_shorts_error_without_ignore_mode() {
+    let parser = args("t").arg(arg("verbose").short('v').flag());
+    let err = parse(parser, &["-vx"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { flag, .. } if flag == "-x"));
+}
+
+fn tar_args() -> Args {
+    args("t")
+        .plus_minus_toggles()
+        .arg(arg("x").short('x').flag())
+        .arg(arg("y").short('y').flag())
+        .arg(arg("file").short('f'))
+}
+
+#[test]
+fn plus_turns_bool_shorts_off() {
+    let m = parse(tar_args(), &["+x"]).unwrap();
+    assert_eq!(m.get_bool_opt("x"), Some(false));
+    let m = parse(tar_args(), &["-x"]).unwrap();
+    assert_eq!(m.get_bool_opt("x"), Some(true));
+}
+
+#[test]
+fn plus_clusters_turn_each_flag_off() {
+    let m = parse(tar_args(), &["+xy"]).unwrap();
+    assert_eq!(m.get_bool_opt("x"), Some(false));
+    assert_eq!(m.get_bool_opt("y"), Some(false));
+}
+
+#[test]
+fn plus_rejects_non_bool_and_unknown_flags() {
+    let err = parse(tar_args(), &["+f"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { .. }), "{:?}", err);
+    let err = parse(tar_args(), &["+z"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { .. }), "{:?}", err);
+}
+
+fn colour_args() -> Args {
+    args("t")
+        .arg(arg("color").long("color").alias("colour").alias("colr"))
+        .arg(arg("fancy").long("fancy").alias("fancier").flag())
+}
+
+#[test]
+fn alias_binds_to_the_same_arg() {
+    let m = parse(colour_args(), &["--colour", "red"]).unwrap();
+    assert_eq!(m.get_string("color"), Some("red"));
+    let m = parse(colour_args(), &["--colr=blue"]).unwrap();
+    assert_eq!(m.get_string("color"), Some("blue"));
+}
+
+#[test]
+fn alias_and_canonical_flag_are_duplicates() {
+    let err = parse(colour_args(), &["--color", "red", "--colour", "blue"]).unwrap_err();
+    assert!(matches!(err, Error::DuplicateValue { .. }), "{:?}", err);
+}
+
+#[test]
+fn negation_works_through_an_alias() {
+    let m = parse(colour_args(), &["--no-fancier"]).unwrap();
+    assert_eq!(m.get_bool_opt("fancy"), Some(false));
+}
+
+#[test]
+fn aliases_are_hidden_from_help() {
+    let Err(Error::Help(text)) = parse(colour_args(), &["--help"]) else {
+        panic!("expected help");
+    };
+    assert!(text.contains("--color"));
+    assert!(!text.contains("colour"));
+    assert!(!text.contains("fancier"));
+}
+
+fn verbose_args() -> Args {
+    args("t").arg(
+        arg("verbose")
+            .short('v')
+            .long("verbose")
+            .count()
+            .max_count(3),
+    )
+}
+
+#[test]
+fn repeated_long_counts_accumulate() {
+    let m = parse(verbose_args(), &["--verbose", "--verbose", "--verbose"]).unwrap();
+    assert_eq!(m.get_count("verbose"), 3);
+}
+
+#[test]
+fn short_and_long_counts_add_up() {
+    let m = parse(verbose_args(), &["-vv", "--verbose"]).unwrap();
+    assert_eq!(m.get_count("verbose"), 3);
+}
+
+#[test]
+fn exceeding_max_count_fails() {
+    let err = parse(verbose_args(), &["-vv", "--verbose", "-v"]).unwrap_err();
+    let Error::CountExceeded { name, max } = err else {
+        panic!("expected CountExceeded, got {:?}", err);
+    };
+    assert_eq!((name.as_str(), max), ("verbose", 3));
+}
+
+fn equals_args() -> Args {
+    args("t")
+        .arg(arg("opt").short('o').long("opt"))
+        .arg(arg("name").long("name"))
+        .arg(crate::pos("file"))
+        .require_equals_for(&["opt"])
+}
+
+#[test]
+fn require_equals_accepts_attached_values() {
+    let m = parse(equals_args(), &["--opt=val", "f"]).unwrap();
+    assert_eq!(m.get_string("opt"), Some("val"));
+    assert_eq!(m.get_string("file"), Some("f"));
+    let m = parse(equals_args(), &["-oval"]).unwrap();
+    assert_eq!(m.get_string("opt"), Some("val"));
+}
+
+#[test]
+fn require_equals_rejects_separate_values() {
+    let err = parse(equals_args(), &["--opt", "val"]).unwrap_err();
+    assert!(
+        matches!(err, Error::RequiresEquals { ref flag } if flag == "--opt"),
+        "{:?}",
+        err
+    );
+    let err = parse(equals_args(), &["-o", "val"]).unwrap_err();
+    assert!(matches!(err, Error::RequiresEquals { .. }), "{:?}", err);
+}
+
+#[test]
+fn require_equals_leaves_other_args_alone() {
+    let m = parse(equals_args(), &["--name", "n"]).unwrap();
+    assert_eq!(m.get_string("name"), Some("n"));
+}
+
+fn case_args(ignore_case: bool) -> Args {
+    args("t")
+        .ignore_case(ignore_case)
+        .arg(arg("verbose").short('v').long("verbose").flag())
+        .arg(arg("version-info").short('V').flag())
+        .arg(
+            arg("color")
+                .long("color")
+                .possible_values(&["always", "never"]),
+        )
+}
+
+#[test]
+fn ignore_case_matches_mixed_case_long_flags() {
+    let m = parse(case_args(true), &["--Verbose", "--COLOR", "never"]).unwrap();
+    assert!(m.get_bool("verbose"));
+    assert_eq!(m.get_string("color"), Some("never"));
+}
+
+#[test]
+fn ignore_case_stores_canonical_possible_values() {
+    let m = parse(case_args(true), &["--color=Always"]).unwrap();
+    assert_eq!(m.get_string("color"), Some("always"));
+}
+
+#[test]
+fn ignore_case_keeps_short_flags_distinct() {
+    let m = parse(case_args(true), &["-V"]).unwrap();
+    assert!(m.get_bool("version-info"));
+    assert!(!m.get_bool("verbose"));
+}
+
+#[test]
+fn case_matters_by_default() {
+    assert!(parse(case_args(false), &["--Verbose"]).is_err());
+    let err = parse(case_args(false), &["--color", "Always"]).unwrap_err();
+    assert!(matches!(err, Error::InvalidChoice { .. }), "{:?}", err);
+}
+
+fn camel_args() -> Args {
+    args("t")
+        .ignore_case(true)
+        .infer_long_args(true)
+        .arg(arg("dry-run").long("dryRun").flag())
+        .arg(arg("dry-level").long("dryLevel"))
+}
+
+#[test]
+fn ignore_case_suggests_the_defined_spelling() {
+    let err = parse(camel_args(), &["--DRYRUX"]).unwrap_err();
+    let Error::UnknownFlagSuggest { suggestion, .. } = err else {
+        panic!("expected a suggestion, got {:?}", err);
+    };
+    assert_eq!(suggestion, "--dryRun");
+}
+
+#[test]
+fn ignore_case_ambiguity_lists_the_defined_spellings() {
+    let err = parse(camel_args(), &["--DRY"]).unwrap_err();
+    let Error::AmbiguousFlag { flag, candidates } = err else {
+        panic!("expected an ambiguity, got {:?}", err);
+    };
+    assert_eq!(flag, "--DRY");
+    assert_eq!(candidates, ["--dryLevel", "--dryRun"]);
+}
+
+#[test]
+fn ignore_case_rejects_flags_differing_only_by_case() {
+    let parser = args("t")
+        .ignore_case(true)
+        .arg(arg("dry-run").long("dryRun").flag())
+        .arg(arg("dryrun").long("dryrun").flag());
+    let err = parse(parser, &[]).unwrap_err();
+    assert!(matches!(err, Error::InvalidDefinition { .. }), "{:?}", err);
+}
@@ -0,0 +1,193 @@
+use super::{parse, temp_file};
+use crate::{Args, Error, arg, args};
+
+fn format_args() -> Args {
+    args("t")
+        .arg(arg("json").long("json").flag())
+        .arg(arg("yaml").long("yaml").flag())
+}
+
+#[test]
+fn require_one_of_picks_the_given_arg() {
+    let m = parse(format_args(), &["--yaml"]).unwrap();
+    assert_eq!(m.require_one_of(&["json", "yaml"]).unwrap(), "yaml");
+}
+
+#[test]
+fn require_one_of_ignores_defaults() {
+    let defs = [
+        arg("json").long("json").flag(),
+        arg("yaml").long("yaml").flag(),
+    ];
+    let m = parse(format_args(), &[]).unwrap().with_defaults(&defs);
+    let err = m.require_one_of(&["json", "yaml"]).unwrap_err();
+    assert!(matches!(err, Error::MissingRequiredGroup { .. }));
+
+    let m = parse(format_args(), &["--json"])
+        .unwrap()
+        .with_defaults(&defs);
+    assert_eq!(m.require_one_of(&["json", "yaml"]).unwrap(), "json");
+}
+
+#[test]
+fn require_one_of_ignores_negated_flags() {
+    let m = parse(format_args(), &["--no-json", "--yaml"]).unwrap();
+    assert_eq!(m.require_one_of(&["json", "yaml"]).unwrap(), "yaml");
+}
+
+#[test]
+fn require_one_of_rejects_two() {
+    let m = parse(format_args(), &["--json", "--yaml"]).unwrap();
+    let err = m.require_one_of(&["json", "yaml"]).unwrap_err();
+    assert!(matches!(err, Error::ConflictingArgs { names } if names == ["json", "yaml"]));
+}
+
+#[test]
+fn require_one_of_resolves_aliases_and_toml_keys() {
+    let parser = args("t")
+        .arg(arg("cert").long("cert").toml_key("tls.cert"))
+        .arg(arg("token").long("token").alias("auth-token"));
+    let m = parse(parser, &[])
+        .unwrap()
+        .with_toml_str("[tls]\ncert = \"a.pem\"")
+        .unwrap();
+    assert_eq!(m.require_one_of(&["cert", "auth-token"]).unwrap(), "cert");
+
+    let parser = args("t")
+        .arg(arg("cert").long("cert").toml_key("tls.cert"))
+        .arg(arg("token").long("token").alias("auth-token"));
+    let m = parse(parser, &["--auth-token", "x"]).unwrap();
+    assert_eq!(
+        m.require_one_of(&["tls.cert", "auth-token"]).unwrap(),
+        "auth-token"
+    );
+}
+
+#[test]
+fn conflicts_rejects_both() {
+    let err = parse(
+        format_args().conflicts("json", "yaml"),
+        &["--json", "--yaml"],
+    )
+    .unwrap_err();
+    assert!(matches!(err, Error::ConflictingArgs { .. }));
+}
+
+#[test]
+fn conflicts_allows_one_or_neither() {
+    let parser = || format_args().conflicts("json", "yaml");
+    assert!(parse(parser(), &["--json"]).is_ok());
+    assert!(parse(parser(), &["--yaml"]).is_ok());
+    assert!(parse(parser(), &[]).is_ok());
+}
+
+#[test]
+fn conflicts_ignores_negated_flags() {
+    let m = parse(
+        format_args().conflicts("json", "yaml"),
+        &["--no-json", "--yaml"],
+    )
+    .unwrap();
+    assert!(m.get_bool("yaml"));
+    assert!(!m.get_bool("json"));
+}
+
+#[test]
+fn conflicts_ignores_defaults() {
+    let parser = args("t")
+        .arg(arg("json").long("json").flag().default(false))
+        .arg(arg("color").long("color").default("auto"))
+        .conflicts("color", "json");
+    assert!(parse(parser, &["--json"]).is_ok());
+}
+
+#[test]
+fn requires_ignores_negated_flags() {
+    let parser = format_args().requires("json", "yaml");
+    assert!(parse(parser, &["--no-json"]).is_ok());
+}
+
+fn tls_args() -> Args {
+    args("t")
+        .arg(arg("cert").long("cert"))
+        .arg(arg("key").long("key"))
+        .arg(arg("ca").long("ca"))
+        .all_or_none(&["cert", "key", "ca"])
+}
+
+#[test]
+fn all_or_none_accepts_all_or_none() {
+    assert!(parse(tls_args(), &[]).is_ok());
+    assert!(parse(tls_args(), &["--cert", "c", "--key", "k", "--ca", "a"]).is_ok());
+}
+
+#[test]
+fn all_or_none_rejects_partial_group() {
+    let err = parse(tls_args(), &["--key", "k", "--cert", "c"]).unwrap_err();
+    let Error::IncompleteGroup { present, missing } = err else {
+        panic!("expected IncompleteGroup, got {:?}", err);
+    };
+    assert_eq!(present, ["cert", "key"]);
+    assert_eq!(missing, ["ca"]);
+}
+
+#[test]
+fn all_or_none_reads_toml_keys() {
+    let path = temp_file("all-or-none.toml", "[tls]\nkey = \"k.pem\"\n");
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("cert").long("cert"))
+        .arg(arg("key").long("key").toml_key("tls.key"))
+        .all_or_none(&["cert", "key"]);
+    let argv = ["-c", path.to_str().unwrap(), "--cert", "c.pem"];
+    assert!(parse(parser, &argv).is_ok());
+}
+
+#[test]
+fn all_or_none_ignores_negated_flags() {
+    let parser = args("t")
+        .arg(arg("tls").long("tls").flag())
+        .arg(arg("cert").long("cert"))
+        .all_or_none(&["tls", "cert"]);
+    assert!(parse(parser, &["--no-tls"]).is_ok());
+}
+
+fn cert_args() -> Args {
+    args("t")
+        .config_arg()
+        .arg(arg("cert").long("cert"))
+        .arg(arg("key").long("key"))
+        .arg(arg("ca").long("ca"))
+        .requires("cert", "key")
+        .requires("cert", "ca")
+}
+
+#[test]
+fn requires_is_satisfied() {
+    assert!(parse(cert_args(), &[]).is_ok());
+    assert!(parse(cert_args(), &["--key", "k"]).is_ok());
+    assert!(parse(cert_args(), &["--cert", "c", "--key", "k", "--ca", "a"]).is_ok());
+}
+
+#[test]
+fn requires_reports_each_missing_dependency() {
+    let err = parse(cert_args(), &["--cert", "c", "--ca", "a"]).unwrap_err();
+    let Error::MissingDependency { arg, needs } = err else {
+        panic!("expected MissingDependency, got {:?}", err);
+    };
+    assert_eq!((arg.as_str(), needs.as_str()), ("cert", "key"));
+
+    let err = parse(cert_args(), &["--cert", "c", "--key", "k"]).unwrap_err();
+    assert!(matches!(err, Error::MissingDependency { ref needs, .. } if needs == "ca"));
+}
+
+#[test]
+fn requires_is_satisfied_by_config() {
+    let path = temp_file("requires.toml", "key = \"k.pem\"\nca = \"ca.pem\"\n");
+    let m = parse(
+        cert_args(),
+        &["-c", path.to_str().unwrap(), "--cert", "c.pem"],
+    )
+    .unwrap();
+    assert_eq!(m.get_string("key"), Some("k.pem"));
+}
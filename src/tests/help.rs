@@ -0,0 +1,62 @@
+use super::parse;
+use crate::{ArgType, Args, Error, arg, args};
+
+/// The `--help` text of `parser`
+fn help(parser: Args) -> String {
+    match parse(parser, &["--help"]) {
+        Err(Error::Help(text)) => text,
+        other => panic!("expected help, got {:?}", other),
+    }
+}
+
+#[test]
+fn value_names_are_all_shown() {
+    let text = help(args("t").arg(arg("point").long("point").value_names(&["x", "y"])));
+    assert!(text.contains("--point <X> <Y>"), "{}", text);
+}
+
+#[test]
+fn num_values_repeats_the_placeholder() {
+    let text = help(args("t").arg(arg("rgb").long("rgb").num_values(3)));
+    assert!(text.contains("--rgb <RGB> <RGB> <RGB>"), "{}", text);
+
+    let text = help(args("t").arg(arg("size").long("size").value_name("n").num_values(2)));
+    assert!(text.contains("--size <N> <N>"), "{}", text);
+}
+
+#[test]
+fn arrays_show_an_ellipsis() {
+    let text = help(args("t").arg(arg("tag").long("tag").arg_type(ArgType::Array)));
+    assert!(text.contains("--tag <TAG>..."), "{}", text);
+}
+
+#[test]
+fn path_args_default_to_a_path_placeholder() {
+    let text = help(args("t").arg(arg("out").long("out").arg_type(ArgType::Path)));
+    assert!(text.contains("--out <PATH>"), "{}", text);
+}
+
+#[test]
+fn ranges_are_annotated() {
+    let text = help(
+        args("t").arg(
+            arg("port")
+                .long("port")
+                .arg_type(ArgType::Integer)
+                .range(1, 65535),
+        ),
+    );
+    assert!(text.contains("[range: 1..=65535]"), "{}", text);
+}
+
+#[test]
+fn possible_values_are_listed() {
+    let text = help(
+        args("t").arg(
+            arg("log-level")
+                .long("log-level")
+                .possible_values(&["debug", "info"]),
+        ),
+    );
+    assert!(text.contains("[possible values: debug, info]"), "{}", text);
+}
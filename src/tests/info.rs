@@ -0,0 +1,37 @@
+use super::parse;
+use crate::{Args, Error, args};
+
+fn app() -> Args {
+    args("app")
+        .version("1.0")
+        .version_details(|| "stoml 0.1\n".to_string())
+}
+
+fn version_text(parser: Args, argv: &[&str]) -> String {
+    match parse(parser, argv) {
+        Err(Error::Version(text)) => text,
+        other => panic!("expected version output, got {:?}", other),
+    }
+}
+
+#[test]
+fn long_version_includes_details() {
+    assert_eq!(version_text(app(), &["--version"]), "app 1.0\nstoml 0.1");
+}
+
+#[test]
+fn short_version_omits_details() {
+    assert_eq!(version_text(app(), &["-V"]), "app 1.0");
+}
+
+#[test]
+fn inferred_version_prefix_is_long() {
+    let text = version_text(app().infer_long_args(true), &["--vers"]);
+    assert_eq!(text, "app 1.0\nstoml 0.1");
+}
+
+#[test]
+fn case_insensitive_version_is_long() {
+    let text = version_text(app().ignore_case(true), &["--VERSION"]);
+    assert_eq!(text, "app 1.0\nstoml 0.1");
+}
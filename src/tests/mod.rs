@@ -0,0 +1,43 @@
+//! Behavior tests through the public API, grouped by feature
+
+mod clusters;
+mod completions;
+mod config;
+#[cfg(feature = "serde")]
+mod de;
+mod definitions;
+mod env;
+mod errors;
+mod events;
+mod flags;
+mod groups;
+mod help;
+mod info;
+mod positionals;
+mod required;
+mod spec;
+mod subcommands;
+mod values;
+
+use crate::{Args, Matches, Result};
+use std::path::PathBuf;
+
+/// Parse `argv` (without the program name)
+fn parse(parser: Args, argv: &[&str]) -> Result<Matches> {
+    parser.parse_from(argv.iter().map(|a| a.to_string()).collect())
+}
+
+/// A path in the temp dir, unique per `name`, with nothing at it yet
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("stoml-args-test-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Write `contents` to a fresh file in the temp dir, unique per `name`
+fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
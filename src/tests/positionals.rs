@@ -0,0 +1,116 @@
+use super::parse;
+use crate::{ArgType, Error, arg, args, pos};
+
+#[test]
+fn variadic_checks_possible_values() {
+    let parser = || args("t").arg(pos("envs").variadic().possible_values(&["dev", "prod"]));
+    let m = parse(parser(), &["dev", "prod"]).unwrap();
+    assert_eq!(m.get_string_vec("envs"), ["dev", "prod"]);
+
+    let err = parse(parser(), &["dev", "qa"]).unwrap_err();
+    assert!(matches!(err, Error::InvalidChoice { value, .. } if value == "qa"));
+}
+
+#[test]
+fn variadic_runs_validator() {
+    let parser = args("t").arg(pos("files").variadic().validator(|v| match v.as_str() {
+        Some(s) if s.ends_with(".rs") => Ok(()),
+        _ => Err("not a Rust file".into()),
+    }));
+    let err = parse(parser, &["main.rs", "notes.txt"]).unwrap_err();
+    assert!(matches!(err, Error::Validation { message, .. } if message == "not a Rust file"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn variadic_checks_pattern() {
+    let parser = args("t").arg(pos("tags").variadic().pattern("^[a-z]+$"));
+    let err = parse(parser, &["ok", "Bad"]).unwrap_err();
+    assert!(matches!(err, Error::PatternMismatch { .. }));
+}
+
+#[test]
+fn group_members_check_range_and_type() {
+    let parser = || {
+        args("t").arg(pos("hosts").positional_group(&[
+            arg("host"),
+            arg("port").arg_type(ArgType::Integer).range(1, 65535),
+        ]))
+    };
+    let m = parse(parser(), &["a", "80", "b", "443"]).unwrap();
+    let records = m.get_records("hosts");
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[1].get("port").and_then(|v| v.as_integer()),
+        Some(443)
+    );
+
+    let err = parse(parser(), &["a", "70000"]).unwrap_err();
+    assert!(matches!(err, Error::OutOfRange { .. }));
+    let err = parse(parser(), &["a", "http"]).unwrap_err();
+    assert!(matches!(err, Error::InvalidValue { .. }));
+}
+
+#[test]
+fn variadic_dedup_after_checks() {
+    let parser = args("t").arg(
+        pos("envs")
+            .variadic()
+            .dedup()
+            .possible_values(&["dev", "prod"]),
+    );
+    let m = parse(parser, &["dev", "prod", "dev"]).unwrap();
+    assert_eq!(m.get_string_vec("envs"), ["dev", "prod"]);
+}
+
+fn numeric_args() -> crate::Args {
+    args("t")
+        .arg(arg("threshold").long("threshold").arg_type(ArgType::Float))
+        .arg(pos("x").arg_type(ArgType::Float))
+}
+
+#[test]
+fn negative_floats_are_values() {
+    for (token, expected) in [("-0.5", -0.5), ("-.5", -0.5), ("-3.14e2", -314.0)] {
+        let m = parse(numeric_args(), &["--threshold", token]).unwrap();
+        assert_eq!(m.get_float("threshold"), Some(expected), "{}", token);
+        let m = parse(numeric_args(), &[token]).unwrap();
+        assert_eq!(m.get_float("x"), Some(expected), "{}", token);
+    }
+}
+
+#[test]
+fn digit_short_flags_still_win() {
+    let parser = || numeric_args().arg(arg("one").short('1').flag());
+    let m = parse(parser(), &["-1"]).unwrap();
+    assert!(m.get_bool("one"));
+    assert_eq!(m.get_float("x"), None);
+
+    let m = parse(parser(), &["-2.5"]).unwrap();
+    assert!(!m.get_bool("one"));
+    assert_eq!(m.get_float("x"), Some(-2.5));
+}
+
+fn offset_args() -> crate::Args {
+    args("t")
+        .arg(arg("offset").long("offset").arg_type(ArgType::Integer))
+        .arg(arg("verbose").short('v').flag())
+        .arg(pos("n").arg_type(ArgType::Integer))
+}
+
+#[test]
+fn negative_integers_are_values() {
+    let m = parse(offset_args(), &["--offset", "-5"]).unwrap();
+    assert_eq!(m.get_integer("offset"), Some(-5));
+    let m = parse(offset_args(), &["-v", "-42"]).unwrap();
+    assert_eq!(m.get_integer("n"), Some(-42));
+    assert!(m.get_bool("verbose"));
+}
+
+#[test]
+fn non_numeric_dash_tokens_are_still_flags() {
+    let m = parse(offset_args(), &["-v"]).unwrap();
+    assert!(m.get_bool("verbose"));
+    let err = parse(offset_args(), &["-5x"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { .. }), "{:?}", err);
+}
@@ -0,0 +1,77 @@
+use super::{parse, temp_file};
+use crate::{ArgType, Args, Error, arg, args};
+
+fn port_args() -> Args {
+    args("t").config_arg().arg(
+        arg("port")
+            .long("port")
+            .arg_type(ArgType::Integer)
+            .default(8080i64)
+            .required(),
+    )
+}
+
+#[test]
+fn default_does_not_satisfy_required() {
+    let err = parse(port_args(), &[]).unwrap_err();
+    assert!(matches!(err, Error::MissingRequired { name } if name == "port"));
+}
+
+#[test]
+fn required_with_default_is_a_definition_problem() {
+    assert!(port_args().validate().is_err());
+}
+
+#[test]
+fn cli_and_config_satisfy_required() {
+    let m = parse(port_args(), &["--port", "1"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(1));
+
+    let path = temp_file("required.toml", "port = 2\n");
+    let m = parse(port_args(), &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(2));
+}
+
+#[test]
+fn env_satisfies_required() {
+    let parser = args("t").arg(
+        arg("token")
+            .long("token")
+            .required()
+            .env("STOML_ARGS_TEST_REQUIRED_TOKEN"),
+    );
+    // SAFETY: the variable name is unique to this test
+    unsafe { std::env::set_var("STOML_ARGS_TEST_REQUIRED_TOKEN", "t") };
+    let m = parse(parser, &[]).unwrap();
+    assert_eq!(m.get_string("token"), Some("t"));
+}
+
+fn token_args() -> Args {
+    args("t")
+        .config_arg()
+        .arg(arg("token").long("token").required_unless("token-file"))
+        .arg(arg("token-file").long("token-file"))
+}
+
+#[test]
+fn required_unless_fails_when_neither_is_given() {
+    let err = parse(token_args(), &[]).unwrap_err();
+    assert!(
+        matches!(err, Error::MissingRequired { ref name } if name == "token"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn required_unless_accepts_either_or_both() {
+    assert!(parse(token_args(), &["--token", "t"]).is_ok());
+    assert!(parse(token_args(), &["--token-file", "f"]).is_ok());
+    assert!(parse(token_args(), &["--token", "t", "--token-file", "f"]).is_ok());
+}
+
+#[test]
+fn required_unless_is_satisfied_by_config() {
+    let path = temp_file("required-unless.toml", "token-file = \"/run/token\"\n");
+    assert!(parse(token_args(), &["-c", path.to_str().unwrap()]).is_ok());
+}
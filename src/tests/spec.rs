@@ -0,0 +1,58 @@
+use super::parse;
+use crate::{ArgType, Args, Error, arg, args, pos};
+
+fn server() -> Args {
+    args("server")
+        .version("1.2.0")
+        .about("Serve \"things\"")
+        .arg(
+            arg("port")
+                .short('p')
+                .long("port")
+                .arg_type(ArgType::UnsignedInteger)
+                .default(8080i64)
+                .help("Port to bind"),
+        )
+        .arg(arg("verbose").short('v').long("verbose").flag())
+        .arg(arg("timeout").long("timeout").arg_type(ArgType::Duration))
+        .arg(arg("token").long("token").required())
+        .arg(
+            pos("root")
+                .arg_type(ArgType::Path)
+                .help("Directory to serve"),
+        )
+}
+
+#[test]
+fn spec_round_trips() {
+    let spec = server().to_spec();
+    let parsed = Args::from_spec(&spec).unwrap();
+    assert_eq!(parsed.to_spec(), spec);
+}
+
+#[test]
+fn spec_parser_behaves_like_the_original() {
+    let parser = Args::from_spec(&server().to_spec()).unwrap();
+    let m = parse(parser, &["-v", "--port", "9000", "--token", "t", "www"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(9000));
+    assert!(m.get_bool("verbose"));
+    assert_eq!(m.get_string("root"), Some("www"));
+
+    let parser = Args::from_spec(&server().to_spec()).unwrap();
+    let err = parse(parser, &["www"]).unwrap_err();
+    assert!(matches!(err, Error::MissingRequired { name } if name == "token"));
+}
+
+#[test]
+fn from_spec_rejects_bad_specs() {
+    for spec in [
+        "version = \"1\"\n",
+        "name = \"t\"\n[[arg]]\nlong = \"x\"\n",
+        "name = \"t\"\n[[arg]]\nname = \"x\"\ntype = \"color\"\n",
+        "name = \"t\"\n[[arg]]\nname = \"x\"\nshort = \"xy\"\n",
+        "name = \"t\"\n[[arg]]\nname = \"x\"\nrequired = \"yes\"\n",
+    ] {
+        let err = Args::from_spec(spec).unwrap_err();
+        assert!(matches!(err, Error::InvalidDefinition { .. }), "{}", spec);
+    }
+}
@@ -0,0 +1,159 @@
+use super::{parse, temp_file};
+use crate::{Error, arg, args};
+
+fn git() -> crate::Args {
+    args("git")
+        .subcommand(
+            args("checkout")
+                .alias("co")
+                .arg(arg("force").short('f').flag()),
+        )
+        .subcommand(args("status").alias("st"))
+}
+
+#[test]
+fn alias_dispatches_to_subcommand() {
+    let m = parse(git(), &["co", "-f"]).unwrap();
+    let (name, sub) = m.subcommand().unwrap();
+    assert_eq!(name, "checkout");
+    assert!(sub.get_bool("force"));
+}
+
+#[test]
+fn canonical_name_still_works() {
+    let m = parse(git(), &["checkout"]).unwrap();
+    assert_eq!(m.subcommand_path(), ["checkout"]);
+}
+
+#[test]
+fn unknown_subcommand_lists_canonical_names() {
+    let err = parse(git(), &["cm"]).unwrap_err();
+    let Error::UnknownSubcommand { available, .. } = err else {
+        panic!("expected UnknownSubcommand, got {:?}", err);
+    };
+    assert_eq!(available, ["checkout", "status"]);
+}
+
+#[test]
+fn unknown_subcommand_suggests_names_and_aliases() {
+    let suggestion = |token: &str| match parse(git(), &[token]).unwrap_err() {
+        Error::UnknownSubcommand { suggestion, .. } => suggestion,
+        err => panic!("expected UnknownSubcommand, got {:?}", err),
+    };
+    assert_eq!(suggestion("chekout").as_deref(), Some("checkout"));
+    assert_eq!(suggestion("sq").as_deref(), Some("st"));
+    assert_eq!(suggestion("frobnicate"), None);
+
+    let err = parse(git(), &["statsu"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unknown subcommand 'statsu' (did you mean 'status'? available: checkout, status)"
+    );
+}
+
+#[test]
+fn aliases_are_hidden_in_help() {
+    let Err(Error::Help(help)) = parse(git(), &["--help"]) else {
+        panic!("expected help");
+    };
+    assert!(help.contains("checkout"));
+    assert!(!help.contains(" co"));
+}
+
+#[test]
+fn alias_clash_is_a_definition_problem() {
+    let parser = args("git")
+        .subcommand(args("checkout").alias("co"))
+        .subcommand(args("commit").alias("co"));
+    assert!(parser.validate().is_err());
+}
+
+fn app() -> crate::Args {
+    args("app")
+        .arg(arg("verbose").short('v').flag())
+        .subcommand(
+            args("server")
+                .about("Run the server")
+                .arg(arg("port").long("port"))
+                .subcommand(args("start").arg(arg("detach").short('d').flag())),
+        )
+        .subcommand(
+            args("db")
+                .about("Database tools")
+                .subcommand(args("migrate")),
+        )
+}
+
+#[test]
+fn nested_subcommands_dispatch() {
+    let m = parse(app(), &["server", "--port", "80", "start", "-d"]).unwrap();
+    assert_eq!(m.subcommand_path(), ["server", "start"]);
+    let (_, server) = m.subcommand().unwrap();
+    assert_eq!(server.get_string("port"), Some("80"));
+    let (_, start) = server.subcommand().unwrap();
+    assert!(start.get_bool("detach"));
+}
+
+#[test]
+fn flags_before_subcommand_bind_to_parent() {
+    let m = parse(app(), &["-v", "db", "migrate"]).unwrap();
+    assert!(m.get_bool("verbose"));
+    let (name, db) = m.subcommand().unwrap();
+    assert_eq!(name, "db");
+    assert!(!db.get_bool("verbose"));
+    assert_eq!(m.subcommand_path(), ["db", "migrate"]);
+}
+
+#[test]
+fn parent_flags_after_subcommand_are_unknown() {
+    let err = parse(app(), &["db", "-v"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownFlag { .. }));
+}
+
+#[test]
+fn help_lists_commands() {
+    let Err(Error::Help(help)) = parse(app(), &["--help"]) else {
+        panic!("expected help");
+    };
+    assert!(help.contains("Usage: app [OPTIONS] [COMMAND]"), "{}", help);
+    assert!(help.contains("Commands:\n  server"), "{}", help);
+    assert!(help.contains("Run the server"));
+    assert!(help.contains("  db"));
+}
+
+#[test]
+fn subcommand_help_names_the_path() {
+    let Err(Error::Help(help)) = parse(app(), &["server", "start", "--help"]) else {
+        panic!("expected help");
+    };
+    assert!(help.starts_with("Usage: app server start"), "{}", help);
+}
+
+#[test]
+fn unknown_subcommand_is_an_error() {
+    let err = parse(app(), &["deploy"]).unwrap_err();
+    assert!(matches!(err, Error::UnknownSubcommand { name, .. } if name == "deploy"));
+}
+
+#[test]
+fn config_scan_stops_at_subcommand() {
+    let path = temp_file("sub-own-c.toml", "port = 1\n");
+    let path = path.to_str().unwrap();
+    let parser = || {
+        args("app").config_arg().subcommand(
+            args("build")
+                .alias("b")
+                .arg(arg("file").short('c'))
+                .arg(arg("x").short('x').flag()),
+        )
+    };
+    let m = parse(parser(), &["build", "-c", path]).unwrap();
+    assert_eq!(m.config_path(), None);
+    assert_eq!(m.subcommand().unwrap().1.get_string("file"), Some(path));
+
+    let m = parse(parser(), &["b", "-xc", path]).unwrap();
+    assert_eq!(m.config_path(), None);
+
+    let m = parse(parser(), &["--", "-c", path]).unwrap();
+    assert_eq!(m.config_path(), None);
+}
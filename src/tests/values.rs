@@ -0,0 +1,694 @@
+use super::{parse, temp_file};
+use crate::{Value, arg, args};
+
+#[test]
+fn filter_prefix_uses_canonical_keys() {
+    let path = temp_file(
+        "filter-prefix.toml",
+        "[tls]\nenabled = true\nkey = \"k.pem\"\n",
+    );
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("cert").long("cert").toml_key("tls.cert"))
+        .arg(arg("key").long("key").toml_key("tls.key"));
+    let m = parse(
+        parser,
+        &[
+            "-c",
+            path.to_str().unwrap(),
+            "--cert",
+            "c.pem",
+            "--key",
+            "cli.pem",
+        ],
+    )
+    .unwrap();
+
+    let tls = m.filter_prefix("tls");
+    assert_eq!(tls.len(), 3);
+    assert_eq!(tls["cert"], Value::String("c.pem".into()));
+    assert_eq!(tls["key"], Value::String("cli.pem".into()));
+    assert_eq!(tls["enabled"], Value::Boolean(true));
+}
+
+#[test]
+fn filter_prefix_needs_a_dot_boundary() {
+    let parser = args("t")
+        .arg(arg("tls").long("tls"))
+        .arg(arg("tlsx").long("tlsx").toml_key("tlsx.mode"));
+    let m = parse(parser, &["--tls", "on", "--tlsx", "strict"]).unwrap();
+    assert!(m.filter_prefix("tls").is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_from_os_keeps_attached_non_utf8_values() {
+    use std::ffi::{OsStr, OsString};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let parser = args("t").arg(arg("file").long("file"));
+    let argv = vec![OsString::from_vec(b"--file=caf\xe9.txt".to_vec())];
+    let m = parser.parse_from_os(argv).unwrap();
+    assert_eq!(m.get_os("file"), Some(OsStr::from_bytes(b"caf\xe9.txt")));
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_from_os_keeps_separate_non_utf8_values() {
+    use std::ffi::{OsStr, OsString};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let parser = args("t").arg(arg("file").long("file"));
+    let argv = vec![
+        OsString::from("--file"),
+        OsString::from_vec(b"caf\xe9.txt".to_vec()),
+    ];
+    let m = parser.parse_from_os(argv).unwrap();
+    assert_eq!(m.get_os("file"), Some(OsStr::from_bytes(b"caf\xe9.txt")));
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_from_os_keeps_each_value_apart() {
+    use std::ffi::{OsStr, OsString};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let parser = args("t").arg(arg("a").long("a")).arg(arg("b").long("b"));
+    let argv = vec![
+        OsString::from_vec(b"--a=\xff".to_vec()),
+        OsString::from_vec(b"--b=\xfe".to_vec()),
+    ];
+    let m = parser.parse_from_os(argv).unwrap();
+    assert_eq!(m.get_os("a"), Some(OsStr::from_bytes(b"\xff")));
+    assert_eq!(m.get_os("b"), Some(OsStr::from_bytes(b"\xfe")));
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_from_os_keeps_array_and_variadic_elements() {
+    use std::ffi::OsString;
+    use std::os::unix::ffi::OsStringExt;
+
+    let parser = args("t")
+        .arg(arg("tag").long("tag").arg_type(crate::ArgType::Array))
+        .arg(crate::pos("files").variadic());
+    let argv = vec![
+        OsString::from("--tag"),
+        OsString::from_vec(b"x\xff".to_vec()),
+        OsString::from_vec(b"--tag=y\xfe".to_vec()),
+        OsString::from("a.txt"),
+        OsString::from_vec(b"b\xe9.txt".to_vec()),
+    ];
+    let m = parser.parse_from_os(argv).unwrap();
+    assert_eq!(
+        m.get_os_vec("tag"),
+        vec![
+            OsString::from_vec(b"x\xff".to_vec()),
+            OsString::from_vec(b"y\xfe".to_vec()),
+        ]
+    );
+    assert_eq!(
+        m.get_os_vec("files"),
+        vec![
+            OsString::from("a.txt"),
+            OsString::from_vec(b"b\xe9.txt".to_vec()),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_from_os_keeps_subcommand_values() {
+    use std::ffi::{OsStr, OsString};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    let parser = args("t")
+        .arg(arg("out").long("out"))
+        .subcommand(args("run").arg(arg("file").long("file")));
+    let argv = vec![
+        OsString::from("--out"),
+        OsString::from_vec(b"\xff".to_vec()),
+        OsString::from("run"),
+        OsString::from("--file"),
+        OsString::from_vec(b"\xfe".to_vec()),
+    ];
+    let m = parser.parse_from_os(argv).unwrap();
+    assert_eq!(m.get_os("out"), Some(OsStr::from_bytes(b"\xff")));
+    let (_, sub) = m.subcommand().unwrap();
+    assert_eq!(sub.get_os("file"), Some(OsStr::from_bytes(b"\xfe")));
+}
+
+#[test]
+fn lenient_replaces_out_of_range_values() {
+    let parser = args("t").lenient().arg(
+        arg("port")
+            .long("port")
+            .arg_type(crate::ArgType::Integer)
+            .range(1, 65535)
+            .default(8080i64),
+    );
+    let m = parse(parser, &["--port", "0"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(8080));
+    assert_eq!(m.source("port"), Some(crate::ValueSource::Default));
+    assert!(matches!(
+        m.warnings(),
+        [crate::Warning::InvalidValue { reason, .. }] if reason == "must be between 1 and 65535"
+    ));
+}
+
+fn ip_args(lenient: bool) -> crate::Args {
+    let host = arg("host").long("host").arg_type(crate::ArgType::IpAddr);
+    args("t").arg(if lenient { host.allow_hostname() } else { host })
+}
+
+#[test]
+fn ip_accepts_v4_and_v6() {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let m = parse(ip_args(false), &["--host", "192.168.1.10"]).unwrap();
+    assert_eq!(
+        m.get_ip("host"),
+        Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)))
+    );
+    let m = parse(ip_args(false), &["--host", "::1"]).unwrap();
+    assert_eq!(m.get_ip("host"), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+}
+
+#[test]
+fn strict_ip_rejects_hostnames_and_garbage() {
+    for bad in ["example.org", "256.1.1.1", "1.2.3", ":::1", ""] {
+        let err = parse(ip_args(false), &["--host", bad]).unwrap_err();
+        assert!(
+            matches!(&err, crate::Error::InvalidValue { expected, .. } if *expected == "an IP address"),
+            "{:?}: {:?}",
+            bad,
+            err
+        );
+    }
+}
+
+#[test]
+fn lenient_ip_accepts_hostnames() {
+    let m = parse(ip_args(true), &["--host", "db-1.example.org"]).unwrap();
+    assert_eq!(m.get_string("host"), Some("db-1.example.org"));
+    assert_eq!(m.get_ip("host"), None);
+
+    let m = parse(ip_args(true), &["--host", "10.0.0.1"]).unwrap();
+    assert!(m.get_ip("host").is_some());
+    assert!(parse(ip_args(true), &["--host", "not a host!"]).is_err());
+}
+
+fn list_args() -> crate::Args {
+    args("t").arg(
+        arg("items")
+            .long("items")
+            .arg_type(crate::ArgType::Array)
+            .value_delimiter(','),
+    )
+}
+
+#[test]
+fn delimiter_split_honors_quotes() {
+    let m = parse(list_args(), &["--items", r#"a,"b,c",d"#]).unwrap();
+    assert_eq!(m.get_string_vec("items"), ["a", "b,c", "d"]);
+
+    let m = parse(list_args(), &["--items", "'x,y',z"]).unwrap();
+    assert_eq!(m.get_string_vec("items"), ["x,y", "z"]);
+}
+
+#[test]
+fn delimiter_split_honors_escapes() {
+    let m = parse(list_args(), &["--items", r"a\,b,c"]).unwrap();
+    assert_eq!(m.get_string_vec("items"), ["a,b", "c"]);
+
+    let m = parse(list_args(), &["--items", r#""say \"hi\"",x"#]).unwrap();
+    assert_eq!(m.get_string_vec("items"), [r#"say "hi""#, "x"]);
+}
+
+#[test]
+fn other_quote_kind_is_literal_inside_quotes() {
+    let m = parse(list_args(), &["--items", r#""it's",ok"#]).unwrap();
+    assert_eq!(m.get_string_vec("items"), ["it's", "ok"]);
+}
+
+#[test]
+fn empty_pieces_are_skipped() {
+    let m = parse(list_args(), &["--items", "a,,b,"]).unwrap();
+    assert_eq!(m.get_string_vec("items"), ["a", "b"]);
+}
+
+fn port_args() -> crate::Args {
+    args("t").arg(
+        arg("port")
+            .long("port")
+            .arg_type(crate::ArgType::Integer)
+            .validator(|v| match v.as_integer() {
+                Some(1..=65535) => Ok(()),
+                _ => Err("port must be between 1 and 65535".into()),
+            }),
+    )
+}
+
+#[test]
+fn validator_accepts_good_values() {
+    let m = parse(port_args(), &["--port", "8080"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(8080));
+}
+
+#[test]
+fn validator_rejects_bad_values() {
+    let err = parse(port_args(), &["--port", "70000"]).unwrap_err();
+    let crate::Error::Validation { name, message } = err else {
+        panic!("expected Validation, got {:?}", err);
+    };
+    assert_eq!(name, "port");
+    assert_eq!(message, "port must be between 1 and 65535");
+}
+
+fn header_args(sep: Option<char>) -> crate::Args {
+    let header = arg("header")
+        .long("header")
+        .arg_type(crate::ArgType::KeyValue);
+    args("t").arg(match sep {
+        Some(c) => header.pair_separator(c),
+        None => header,
+    })
+}
+
+#[test]
+fn pairs_split_on_equals_by_default() {
+    let m = parse(header_args(None), &["--header", "a=1", "--header", "b=x=y"]).unwrap();
+    let map = m.get_map("header").unwrap();
+    assert_eq!(map.get("a"), Some(&Value::String("1".into())));
+    assert_eq!(map.get("b"), Some(&Value::String("x=y".into())));
+}
+
+#[test]
+fn pairs_split_on_custom_separator() {
+    let m = parse(
+        header_args(Some(':')),
+        &[
+            "--header",
+            "Accept:application/json",
+            "--header",
+            "Host:a:8080",
+        ],
+    )
+    .unwrap();
+    let map = m.get_map("header").unwrap();
+    assert_eq!(
+        map.get("Accept"),
+        Some(&Value::String("application/json".into()))
+    );
+    assert_eq!(map.get("Host"), Some(&Value::String("a:8080".into())));
+}
+
+#[test]
+fn pairs_without_separator_fail() {
+    let err = parse(header_args(Some(':')), &["--header", "Accept=json"]).unwrap_err();
+    let crate::Error::MissingSeparator {
+        name, separator, ..
+    } = err
+    else {
+        panic!("expected MissingSeparator, got {:?}", err);
+    };
+    assert_eq!((name.as_str(), separator), ("header", ':'));
+}
+
+#[test]
+fn delimited_and_repeated_values_mix() {
+    let m = parse(
+        list_args(),
+        &["--items", "a,b", "--items", "c", "--items=d,e"],
+    )
+    .unwrap();
+    assert_eq!(m.get_string_vec("items"), ["a", "b", "c", "d", "e"]);
+}
+
+#[test]
+fn non_arrays_ignore_the_delimiter() {
+    let parser = args("t").arg(arg("name").long("name").value_delimiter(','));
+    let m = parse(parser, &["--name", "a,b"]).unwrap();
+    assert_eq!(m.get_string("name"), Some("a,b"));
+}
+
+#[test]
+fn typed_vecs_over_a_repeated_flag() {
+    let parser = args("t").arg(
+        arg("feature")
+            .long("feature")
+            .arg_type(crate::ArgType::Array),
+    );
+    let m = parse(parser, &["--feature", "tls", "--feature", "zstd"]).unwrap();
+    assert_eq!(m.get_string_vec("feature"), ["tls", "zstd"]);
+    assert!(m.get_integer_vec("feature").is_empty());
+    assert!(m.get_string_vec("missing").is_empty());
+}
+
+#[test]
+fn typed_vecs_over_a_variadic_positional() {
+    let parser = args("t").arg(crate::pos("files").variadic());
+    let m = parse(parser, &["a.rs", "b.rs"]).unwrap();
+    assert_eq!(m.get_string_vec("files"), ["a.rs", "b.rs"]);
+    assert_eq!(m.array_len("files"), 2);
+}
+
+#[test]
+fn typed_vecs_skip_other_element_types() {
+    let path = temp_file("typed-vecs.toml", "nums = [1, 2.5, \"x\", 3]\n");
+    let parser = args("t")
+        .config_arg()
+        .arg(arg("nums").long("nums").arg_type(crate::ArgType::Array));
+    let m = parse(parser, &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(m.get_integer_vec("nums"), [1, 3]);
+    assert_eq!(m.get_float_vec("nums"), [2.5]);
+    assert_eq!(m.get_string_vec("nums"), ["x"]);
+}
+
+fn path_args() -> crate::Args {
+    args("t")
+        .config_arg()
+        .arg(arg("out").long("out").arg_type(crate::ArgType::Path))
+}
+
+#[test]
+fn get_path_from_cli() {
+    let m = parse(path_args(), &["--out", "build/out.bin"]).unwrap();
+    assert_eq!(
+        m.get_path("out"),
+        Some(std::path::PathBuf::from("build/out.bin"))
+    );
+}
+
+#[test]
+fn get_path_from_config() {
+    let path = temp_file("get-path.toml", "out = \"/var/tmp/out.bin\"\n");
+    let m = parse(path_args(), &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(
+        m.get_path("out"),
+        Some(std::path::PathBuf::from("/var/tmp/out.bin"))
+    );
+}
+
+#[test]
+fn get_path_missing_is_none() {
+    let m = parse(path_args(), &[]).unwrap();
+    assert_eq!(m.get_path("out"), None);
+}
+
+fn message_args() -> crate::Args {
+    args("t")
+        .arg(arg("verbose").short('v').flag())
+        .arg(arg("message").short('m').long("message").rest_of_line())
+}
+
+#[test]
+fn rest_of_line_joins_remaining_tokens() {
+    let m = parse(
+        message_args(),
+        &["-v", "--message", "hello", "world", "foo"],
+    )
+    .unwrap();
+    assert_eq!(m.get_string("message"), Some("hello world foo"));
+    assert!(m.get_bool("verbose"));
+
+    let m = parse(message_args(), &["-m", "fix", "-v", "--now"]).unwrap();
+    assert_eq!(m.get_string("message"), Some("fix -v --now"));
+    assert!(!m.get_bool("verbose"));
+}
+
+#[test]
+fn rest_of_line_stops_at_double_dash() {
+    let m = parse(message_args(), &["--message", "a", "b", "--", "c"]).unwrap();
+    assert_eq!(m.get_string("message"), Some("a b"));
+    assert_eq!(m.remaining(), ["c"]);
+}
+
+fn int_value(value: &str) -> crate::Result<i64> {
+    let parser = args("t").arg(arg("n").long("n").arg_type(crate::ArgType::Integer));
+    Ok(parse(parser, &["--n", value])?.get_integer("n").unwrap())
+}
+
+#[test]
+fn integers_accept_radix_prefixes() {
+    assert_eq!(int_value("0x1F").unwrap(), 31);
+    assert_eq!(int_value("0o17").unwrap(), 15);
+    assert_eq!(int_value("0b1010").unwrap(), 10);
+    assert_eq!(int_value("-0x10").unwrap(), -16);
+}
+
+#[test]
+fn integers_accept_underscore_grouping() {
+    assert_eq!(int_value("1_000_000").unwrap(), 1_000_000);
+    assert_eq!(int_value("0xFF_FF").unwrap(), 0xFFFF);
+}
+
+#[test]
+fn malformed_integers_are_invalid() {
+    for bad in ["0x1G", "0b102", "0x", "_1", "1_", "0x-1"] {
+        let err = int_value(bad).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::InvalidValue {
+                    expected: "an integer",
+                    ..
+                }
+            ),
+            "{}: {:?}",
+            bad,
+            err
+        );
+    }
+}
+
+fn byte_value(value: &str) -> crate::Result<i64> {
+    let parser = args("t").arg(arg("size").long("size").arg_type(crate::ArgType::Bytes));
+    Ok(parse(parser, &["--size", value])?
+        .get_integer("size")
+        .unwrap())
+}
+
+#[test]
+fn byte_sizes_without_unit_are_bytes() {
+    assert_eq!(byte_value("512").unwrap(), 512);
+    assert_eq!(byte_value("512B").unwrap(), 512);
+}
+
+#[test]
+fn byte_sizes_decimal_units() {
+    assert_eq!(byte_value("1KB").unwrap(), 1000);
+    assert_eq!(byte_value("10MB").unwrap(), 10_000_000);
+    assert_eq!(byte_value("1GB").unwrap(), 1_000_000_000);
+}
+
+#[test]
+fn byte_sizes_binary_units() {
+    assert_eq!(byte_value("4K").unwrap(), 4096);
+    assert_eq!(byte_value("2MiB").unwrap(), 2 << 20);
+    assert_eq!(byte_value("1GiB").unwrap(), 1 << 30);
+    assert_eq!(byte_value("1.5K").unwrap(), 1536);
+}
+
+#[test]
+fn byte_sizes_ignore_case() {
+    assert_eq!(byte_value("2mib").unwrap(), 2 << 20);
+    assert_eq!(byte_value("1kb").unwrap(), 1000);
+    assert_eq!(byte_value("1Gb").unwrap(), 1_000_000_000);
+}
+
+#[test]
+fn malformed_byte_sizes_are_invalid() {
+    for bad in ["10XB", "MB", "1.2.3K", "-5K"] {
+        let err = byte_value(bad).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::InvalidValue {
+                    expected: "a byte size",
+                    ..
+                }
+            ),
+            "{}: {:?}",
+            bad,
+            err
+        );
+    }
+}
+
+fn duration_args() -> crate::Args {
+    args("t").config_arg().arg(
+        arg("timeout")
+            .long("timeout")
+            .arg_type(crate::ArgType::Duration),
+    )
+}
+
+fn duration(value: &str) -> crate::Result<Option<std::time::Duration>> {
+    Ok(parse(duration_args(), &["--timeout", value])?.get_duration("timeout"))
+}
+
+#[test]
+fn durations_with_single_units() {
+    use std::time::Duration;
+    assert_eq!(duration("30s").unwrap(), Some(Duration::from_secs(30)));
+    assert_eq!(duration("5m").unwrap(), Some(Duration::from_secs(300)));
+    assert_eq!(duration("2h").unwrap(), Some(Duration::from_secs(7200)));
+    assert_eq!(duration("500ms").unwrap(), Some(Duration::from_millis(500)));
+    assert_eq!(duration("45").unwrap(), Some(Duration::from_secs(45)));
+    assert_eq!(duration("1.5").unwrap(), Some(Duration::from_millis(1500)));
+    assert_eq!(duration(".5").unwrap(), Some(Duration::from_millis(500)));
+}
+
+#[test]
+fn compound_durations_sum() {
+    use std::time::Duration;
+    assert_eq!(duration("1h30m").unwrap(), Some(Duration::from_secs(5400)));
+    assert_eq!(
+        duration("1m30s500ms").unwrap(),
+        Some(Duration::from_millis(90_500))
+    );
+}
+
+#[test]
+fn durations_from_config_strings() {
+    let path = temp_file("duration.toml", "timeout = \"1h30m\"\n");
+    let m = parse(duration_args(), &["-c", path.to_str().unwrap()]).unwrap();
+    assert_eq!(
+        m.get_duration("timeout"),
+        Some(std::time::Duration::from_secs(5400))
+    );
+}
+
+#[test]
+fn malformed_durations_are_invalid() {
+    for bad in [
+        "5y", "m", "1h30", "", "1.2.3s", "1.2.3", ".", "1.5x", "1e3", "inf", "nan", "-1s", "1 h",
+        "h1",
+    ] {
+        let err = duration(bad).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::InvalidValue {
+                    expected: "a duration",
+                    ..
+                }
+            ),
+            "{:?}: {:?}",
+            bad,
+            err
+        );
+    }
+}
+
+fn range_args() -> crate::Args {
+    args("t")
+        .arg(
+            arg("port")
+                .long("port")
+                .arg_type(crate::ArgType::Integer)
+                .range(1, 65535),
+        )
+        .arg(
+            arg("ratio")
+                .long("ratio")
+                .arg_type(crate::ArgType::Float)
+                .range_f64(0.0, 1.0),
+        )
+}
+
+#[test]
+fn in_range_values_are_accepted() {
+    let m = parse(range_args(), &["--port", "1", "--ratio", "1.0"]).unwrap();
+    assert_eq!(m.get_integer("port"), Some(1));
+    assert_eq!(m.get_float("ratio"), Some(1.0));
+    assert!(parse(range_args(), &["--port", "65535", "--ratio", "0"]).is_ok());
+}
+
+#[test]
+fn values_below_min_are_out_of_range() {
+    let err = parse(range_args(), &["--port", "0"]).unwrap_err();
+    let crate::Error::OutOfRange {
+        name,
+        value,
+        min,
+        max,
+    } = err
+    else {
+        panic!("expected OutOfRange, got {:?}", err);
+    };
+    assert_eq!(
+        (name.as_str(), value.as_str(), min.as_str(), max.as_str()),
+        ("port", "0", "1", "65535")
+    );
+}
+
+#[test]
+fn values_above_max_are_out_of_range() {
+    let err = parse(range_args(), &["--port", "65536"]).unwrap_err();
+    assert!(matches!(err, crate::Error::OutOfRange { .. }), "{:?}", err);
+    let err = parse(range_args(), &["--ratio", "1.5"]).unwrap_err();
+    assert!(
+        matches!(err, crate::Error::OutOfRange { ref name, .. } if name == "ratio"),
+        "{:?}",
+        err
+    );
+}
+
+fn feature_args(dedup: bool) -> crate::Args {
+    let feature = arg("feature")
+        .long("feature")
+        .arg_type(crate::ArgType::Array);
+    args("t").arg(if dedup { feature.dedup() } else { feature })
+}
+
+#[test]
+fn dedup_collapses_repeated_values() {
+    let m = parse(
+        feature_args(true),
+        &["--feature", "x", "--feature", "y", "--feature", "x"],
+    )
+    .unwrap();
+    assert_eq!(m.get_string_vec("feature"), ["x", "y"]);
+}
+
+#[test]
+fn repeated_values_are_kept_without_dedup() {
+    let m = parse(feature_args(false), &["--feature", "x", "--feature", "x"]).unwrap();
+    assert_eq!(m.get_string_vec("feature"), ["x", "x"]);
+}
+
+fn level_args() -> crate::Args {
+    args("t").arg(
+        arg("log-level")
+            .long("log-level")
+            .possible_values(&["debug", "info", "warn", "error"]),
+    )
+}
+
+#[test]
+fn possible_values_accept_listed_values() {
+    let m = parse(level_args(), &["--log-level", "warn"]).unwrap();
+    assert_eq!(m.get_string("log-level"), Some("warn"));
+}
+
+#[test]
+fn possible_values_reject_other_values() {
+    let err = parse(level_args(), &["--log-level", "trace"]).unwrap_err();
+    assert!(
+        err.to_string().contains("debug, info, warn, error"),
+        "{}",
+        err
+    );
+    let crate::Error::InvalidChoice {
+        name,
+        value,
+        allowed,
+    } = err
+    else {
+        panic!("expected InvalidChoice, got {:?}", err);
+    };
+    assert_eq!((name.as_str(), value.as_str()), ("log-level", "trace"));
+    assert_eq!(allowed, ["debug", "info", "warn", "error"]);
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+/// A non-fatal issue noticed while resolving argument values
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A config value's type disagrees with the CLI value or declared type
+    TypeMismatch {
+        key: String,
+        arg: String,
+        expected: &'static str,
+        got: &'static str,
+    },
+
+    /// A deprecated argument was used
+    Deprecated { arg: String, message: String },
+
+    /// An unparseable value was replaced by the default (see `Args::lenient`)
+    InvalidValue {
+        arg: String,
+        value: String,
+        reason: String,
+    },
+
+    /// Two present arguments imply different values for the same argument
+    ConflictingImplication {
+        target: String,
+        first: String,
+        second: String,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::TypeMismatch {
+                key,
+                arg,
+                expected,
+                got,
+            } => {
+                write!(
+                    f,
+                    "config key '{}' for argument '{}' is {}, expected {}",
+                    key, arg, got, expected
+                )
+            }
+            Warning::Deprecated { arg, message } => {
+                write!(f, "argument '{}' is {}", arg, message)
+            }
+            Warning::InvalidValue { arg, value, reason } => {
+                write!(
+                    f,
+                    "invalid value '{}' for '{}' ({}); using the default",
+                    value, arg, reason
+                )
+            }
+            Warning::ConflictingImplication {
+                target,
+                first,
+                second,
+            } => {
+                write!(
+                    f,
+                    "'{}' and '{}' imply different values for '{}'; using '{}'",
+                    first, second, target, first
+                )
+            }
+        }
+    }
+}